@@ -0,0 +1,61 @@
+//! Times `to_bytes`/`from_bytes` on a large array.
+//!
+//! Compare `cargo bench --bench bincode --features bincode` with
+//! `cargo bench --bench bincode --features bincode,zerocopy` to measure the
+//! element-wise and byte string data encodings.
+extern crate arrayfire;
+extern crate arrayfire_serde;
+
+use arrayfire::{Array, Dim4};
+use arrayfire_serde::{from_bytes, to_bytes};
+use std::time::{Duration, Instant};
+
+const ELEMENTS: u64 = 1 << 20;
+const ITERATIONS: u32 = 10;
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs() as f64 * 1e3 + f64::from(duration.subsec_nanos()) / 1e6
+}
+
+fn bench(name: &str, array: &Array) {
+    let start = Instant::now();
+    let mut bytes = Vec::new();
+    for _ in 0..ITERATIONS {
+        bytes = to_bytes(array).unwrap();
+    }
+    let ser = start.elapsed() / ITERATIONS;
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        from_bytes(&bytes).unwrap();
+    }
+    let de = start.elapsed() / ITERATIONS;
+
+    println!(
+        "{:<8} {:>12} bytes  serialize {:>8.2} ms  deserialize {:>8.2} ms",
+        name,
+        bytes.len(),
+        millis(ser),
+        millis(de)
+    );
+}
+
+fn main() {
+    let dim = Dim4::new(&[ELEMENTS, 1, 1, 1]);
+    println!(
+        "{} elements, zerocopy {}",
+        ELEMENTS,
+        if cfg!(feature = "zerocopy") {
+            "on"
+        } else {
+            "off"
+        }
+    );
+
+    let values: Vec<f32> = (0..ELEMENTS).map(|i| i as f32).collect();
+    bench("f32", &Array::new(&values, dim));
+    let values: Vec<f64> = (0..ELEMENTS).map(|i| i as f64).collect();
+    bench("f64", &Array::new(&values, dim));
+    let values: Vec<i32> = (0..ELEMENTS).map(|i| i as i32).collect();
+    bench("s32", &Array::new(&values, dim));
+}
@@ -0,0 +1,82 @@
+//! Compares the element-wise and packed `Array` representations with `bincode`.
+//!
+//! Run with `cargo bench --features bincode`.
+extern crate arrayfire;
+extern crate arrayfire_serde;
+extern crate bincode;
+extern crate serde;
+
+use arrayfire::{Array, Dim4};
+use arrayfire_serde::{deserialize, deserialize_packed, serialize, serialize_packed};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::time::{Duration, Instant};
+
+const ELEMENTS: u64 = 1 << 20;
+const ITERATIONS: u32 = 10;
+
+struct Tuple(Array);
+
+impl Serialize for Tuple {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Tuple {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize(deserializer).map(Tuple)
+    }
+}
+
+struct Packed(Array);
+
+impl Serialize for Packed {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_packed(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Packed {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_packed(deserializer).map(Packed)
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs() as f64 * 1e3 + f64::from(duration.subsec_nanos()) / 1e6
+}
+
+fn bench<T>(name: &str, value: &T)
+where
+    T: Serialize + for<'de> Deserialize<'de>,
+{
+    let start = Instant::now();
+    let mut bytes = Vec::new();
+    for _ in 0..ITERATIONS {
+        bytes = bincode::serialize(value).unwrap();
+    }
+    let ser = start.elapsed() / ITERATIONS;
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        bincode::deserialize::<T>(&bytes).unwrap();
+    }
+    let de = start.elapsed() / ITERATIONS;
+
+    println!(
+        "{:<8} {:>12} bytes  serialize {:>8.2} ms  deserialize {:>8.2} ms",
+        name,
+        bytes.len(),
+        millis(ser),
+        millis(de)
+    );
+}
+
+fn main() {
+    let values: Vec<f64> = (0..ELEMENTS).map(|i| i as f64).collect();
+    let array = Array::new(&values, Dim4::new(&[ELEMENTS, 1, 1, 1]));
+
+    println!("f64 array of {} elements, bincode", ELEMENTS);
+    bench("tuple", &Tuple(array.clone()));
+    bench("packed", &Packed(array));
+}
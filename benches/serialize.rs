@@ -0,0 +1,106 @@
+//! Measures serialize and deserialize throughput of the element-wise tuple,
+//! packed and binary `Array` representations for a range of array sizes.
+//!
+//! Run with `cargo bench --bench serialize --features bincode`, adding
+//! `zerocopy` to compare the byte data path. Throughput is reported in bytes
+//! of array data, so the sizes are directly comparable.
+extern crate arrayfire;
+extern crate arrayfire_serde;
+extern crate bincode;
+#[macro_use]
+extern crate criterion;
+extern crate serde;
+
+use arrayfire::{Array, Dim4, HasAfEnum};
+use arrayfire_serde::{
+    deserialize, deserialize_packed, read_array, serialize, serialize_packed, write_array,
+};
+use criterion::{Criterion, Throughput};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::mem;
+
+/// Array sizes in elements: 1K, 1M and 16M.
+const SIZES: [u64; 3] = [1 << 10, 1 << 20, 1 << 24];
+
+struct Tuple(Array);
+
+impl Serialize for Tuple {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Tuple {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize(deserializer).map(Tuple)
+    }
+}
+
+struct Packed(Array);
+
+impl Serialize for Packed {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_packed(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Packed {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_packed(deserializer).map(Packed)
+    }
+}
+
+fn bench<T: HasAfEnum>(c: &mut Criterion, name: &str, values: &[T]) {
+    let elements = values.len() as u64;
+    let array = Array::new(values, Dim4::new(&[elements, 1, 1, 1]));
+    let mut group = c.benchmark_group(format!("{}/{}", name, elements));
+    group.throughput(Throughput::Bytes(mem::size_of_val(values) as u64));
+    if elements >= 1 << 24 {
+        group.sample_size(10);
+    }
+
+    let tuple = Tuple(array.clone());
+    let bytes = bincode::serialize(&tuple).unwrap();
+    group.bench_function("tuple/serialize", |b| {
+        b.iter(|| bincode::serialize(&tuple).unwrap())
+    });
+    group.bench_function("tuple/deserialize", |b| {
+        b.iter(|| bincode::deserialize::<Tuple>(&bytes).unwrap())
+    });
+
+    let packed = Packed(array.clone());
+    let bytes = bincode::serialize(&packed).unwrap();
+    group.bench_function("packed/serialize", |b| {
+        b.iter(|| bincode::serialize(&packed).unwrap())
+    });
+    group.bench_function("packed/deserialize", |b| {
+        b.iter(|| bincode::deserialize::<Packed>(&bytes).unwrap())
+    });
+
+    let mut bytes = Vec::new();
+    write_array(&mut bytes, &array).unwrap();
+    let mut buffer = Vec::with_capacity(bytes.len());
+    group.bench_function("binary/serialize", |b| {
+        b.iter(|| {
+            buffer.clear();
+            write_array(&mut buffer, &array).unwrap();
+        })
+    });
+    group.bench_function("binary/deserialize", |b| {
+        b.iter(|| read_array(&mut &bytes[..]).unwrap())
+    });
+
+    group.finish();
+}
+
+fn serialize_benches(c: &mut Criterion) {
+    for &elements in &SIZES {
+        let values: Vec<f32> = (0..elements).map(|i| i as f32).collect();
+        bench(c, "f32", &values);
+        let values: Vec<f64> = (0..elements).map(|i| i as f64).collect();
+        bench(c, "f64", &values);
+    }
+}
+
+criterion_group!(benches, serialize_benches);
+criterion_main!(benches);
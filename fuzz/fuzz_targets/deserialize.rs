@@ -0,0 +1,16 @@
+//! Feeds arbitrary bytes to `from_slice`, which must return `Ok` or `Err` and
+//! never panic, however corrupt the header or data.
+//!
+//! Run with `cargo fuzz run deserialize` from the repository root. Seeding the
+//! corpus with a few files written by `write_array` speeds up finding
+//! well-formed headers.
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate arrayfire_serde;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok((_, len)) = arrayfire_serde::from_slice(data) {
+        assert!(len <= data.len());
+    }
+});
@@ -0,0 +1,61 @@
+//! Conversion of `Array` data to Apache Arrow buffers.
+
+use alloc::sync::Arc;
+use arrayfire::{Array, DType};
+use arrow_buffer::Buffer;
+use arrow_schema::{DataType, Field};
+
+use super::packed::with_host_bytes;
+use super::{array_dtype, Error};
+
+/// Maps `dtype` to the Arrow type of a buffer holding its elements.
+///
+/// Complex elements have no Arrow counterpart and map to a fixed size list of
+/// `(re, im)` pairs, whose child values are the buffer contents.
+fn arrow_type(dtype: DType) -> DataType {
+    match dtype {
+        DType::F32 => DataType::Float32,
+        DType::F64 => DataType::Float64,
+        DType::B8 => DataType::Boolean,
+        DType::S16 => DataType::Int16,
+        DType::S32 => DataType::Int32,
+        DType::S64 => DataType::Int64,
+        DType::U8 => DataType::UInt8,
+        DType::U16 => DataType::UInt16,
+        DType::U32 => DataType::UInt32,
+        DType::U64 => DataType::UInt64,
+        DType::C32 => complex_type(DataType::Float32),
+        DType::C64 => complex_type(DataType::Float64),
+    }
+}
+
+fn complex_type(component: DataType) -> DataType {
+    DataType::FixedSizeList(Arc::new(Field::new("item", component, false)), 2)
+}
+
+/// Copies the elements of `array` in column-major order into an Arrow
+/// `Buffer`, returned with the Arrow type of its values.
+///
+/// The buffer is in host byte order, as Arrow requires. `B8` arrays become a
+/// bit packed `Boolean` buffer with the least significant bit first, and
+/// complex arrays a `FixedSizeList` of two floats per element.
+///
+/// Requires the `arrow` feature.
+///
+/// ```rust
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+/// extern crate arrow_schema;
+///
+/// # fn main() {
+/// let array = arrayfire::Array::new(&[1.0f32, 2.0], arrayfire::Dim4::new(&[2, 1, 1, 1]));
+/// let (data_type, buffer) = arrayfire_serde::to_arrow_buffer(&array).unwrap();
+/// assert_eq!(data_type, arrow_schema::DataType::Float32);
+/// assert_eq!(buffer.typed_data::<f32>(), &[1.0, 2.0]);
+/// # }
+/// ```
+pub fn to_arrow_buffer(array: &Array) -> Result<(DataType, Buffer), Error> {
+    let dtype = array_dtype(array)?;
+    let buffer = with_host_bytes(array, |bytes| Buffer::from_slice_ref(bytes))?;
+    Ok((arrow_type(dtype), buffer))
+}
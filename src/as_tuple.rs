@@ -0,0 +1,53 @@
+//! `serde_with` adapter for `Array`.
+
+use arrayfire::Array;
+use serde::{Deserializer, Serializer};
+use serde_with::{DeserializeAs, SerializeAs};
+
+/// Adapter for `serde_with::serde_as` writing an `Array` in the
+/// `(version, dtype, dims, data)` tuple representation of `serialize`.
+///
+/// Unlike `#[serde(with = "arrayfire_serde")]`, it composes with the
+/// adapters of `serde_with`, so `Vec`, `Option` and map fields holding arrays
+/// need no module of their own:
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate serde;
+/// extern crate serde_with;
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// use arrayfire_serde::ArrayAsTuple;
+/// use serde_with::serde_as;
+///
+/// #[serde_as]
+/// #[derive(Serialize, Deserialize)]
+/// struct Model {
+///     #[serde_as(as = "ArrayAsTuple")]
+///     weights: arrayfire::Array,
+///     #[serde_as(as = "Option<Vec<ArrayAsTuple>>")]
+///     layers: Option<Vec<arrayfire::Array>>,
+/// }
+/// # fn main() {}
+/// ```
+pub struct ArrayAsTuple;
+
+impl SerializeAs<Array> for ArrayAsTuple {
+    fn serialize_as<S>(source: &Array, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        super::serialize(source, serializer)
+    }
+}
+
+impl<'de> DeserializeAs<'de, Array> for ArrayAsTuple {
+    fn deserialize_as<D>(deserializer: D) -> Result<Array, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::deserialize(deserializer)
+    }
+}
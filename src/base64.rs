@@ -0,0 +1,204 @@
+//! `Array` representation with the element data as a base64 string.
+
+use arrayfire::{Array, DType, Dim4};
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+use serde::ser::{self, SerializeStruct};
+use serde::{Deserializer, Serializer};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::packed::{build_array, swap_little_endian, with_host_bytes};
+use super::{
+    array_dtype, backend_call, check_size, check_version, empty_array, ArrayField, De, Ser,
+    ARRAY_FIELDS, FORMAT_VERSION,
+};
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Reads the next map key, failing unless it is `field`.
+fn expect_key<'de, V>(map: &mut V, field: ArrayField) -> Result<(), V::Error>
+where
+    V: MapAccess<'de>,
+{
+    match map.next_key::<ArrayField>()? {
+        Some(ref key) if *key == field => Ok(()),
+        Some(key) => Err(de::Error::custom(format!(
+            "expected field `{}`, found `{}`",
+            field.name(),
+            key.name()
+        ))),
+        None => Err(de::Error::missing_field(field.name())),
+    }
+}
+
+/// Encodes `bytes` with the standard base64 alphabet and `=` padding.
+fn encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).cloned().unwrap_or(0),
+            chunk.get(2).cloned().unwrap_or(0),
+        ];
+        let n = u32::from(b[0]) << 16 | u32::from(b[1]) << 8 | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn decode_char(c: u8) -> Option<u32> {
+    ALPHABET.iter().position(|&a| a == c).map(|i| i as u32)
+}
+
+/// Decodes a padded standard base64 string.
+fn decode(s: &str) -> Result<Vec<u8>, String> {
+    let s = s.as_bytes();
+    if !s.len().is_multiple_of(4) {
+        return Err(format!("invalid base64 length {}", s.len()));
+    }
+    let mut out = Vec::with_capacity(s.len() / 4 * 3);
+    let last = s.len() / 4;
+    for (i, chunk) in s.chunks(4).enumerate() {
+        let padding = if i + 1 == last {
+            chunk.iter().rev().take_while(|&&c| c == b'=').count()
+        } else {
+            0
+        };
+        if padding > 2 {
+            return Err("invalid base64 padding".to_string());
+        }
+        let mut n = 0u32;
+        for &c in &chunk[..4 - padding] {
+            let value = decode_char(c)
+                .ok_or_else(|| format!("invalid base64 character {:?}", c as char))?;
+            n = n << 6 | value;
+        }
+        n <<= 6 * padding as u32;
+        let bytes = [(n >> 16) as u8, (n >> 8) as u8, n as u8];
+        out.extend_from_slice(&bytes[..3 - padding]);
+    }
+    Ok(out)
+}
+
+/// Serializes an `Array` as a struct with named `version`, `dtype`, `dims`
+/// and `data` fields, where `data` is a base64 string of the little-endian
+/// element data.
+///
+/// The data bytes are laid out as in `serialize_packed`, with `B8` arrays bit
+/// packed. Compared to `serialize_human` this keeps large arrays compact and
+/// fast to parse in JSON. The `data` field is omitted for empty arrays.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate serde;
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct MyStruct {
+///     #[serde(serialize_with = "arrayfire_serde::serialize_base64")]
+///     #[serde(deserialize_with = "arrayfire_serde::deserialize_base64")]
+///     tensor: arrayfire::Array,
+/// }
+/// # fn main() {}
+/// ```
+pub fn serialize_base64<S>(array: &Array, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    backend_call(|| array.eval()).map_err(ser::Error::custom)?;
+    let dim = array.dims();
+    let dtype = array_dtype(array).map_err(ser::Error::custom)?;
+    let empty = dim.elements() == 0;
+
+    let mut state = serializer.serialize_struct("Array", if empty { 3 } else { 4 })?;
+    state.serialize_field("version", &FORMAT_VERSION)?;
+    state.serialize_field("dtype", &Ser::new(&dtype))?;
+    state.serialize_field("dims", &Ser::new(&dim))?;
+    if !empty {
+        let data = with_host_bytes(array, |bytes| {
+            let mut bytes = bytes.to_vec();
+            swap_little_endian(&mut bytes, dtype);
+            encode(&bytes)
+        })
+        .map_err(ser::Error::custom)?;
+        state.serialize_field("data", &data)?;
+    }
+    state.end()
+}
+
+/// Builds an array from the base64 encoded little-endian `data`.
+fn decode_array<E: de::Error>(dtype: DType, dim: Dim4, data: &str) -> Result<Array, E> {
+    let mut bytes = decode(data).map_err(E::custom)?;
+    swap_little_endian(&mut bytes, dtype);
+    build_array(dtype, dim, &bytes).map_err(E::custom)
+}
+
+/// Deserializes an `Array` written by `serialize_base64`.
+pub fn deserialize_base64<'de, D>(deserializer: D) -> Result<Array, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct Base64Visitor;
+
+    impl<'de> Visitor<'de> for Base64Visitor {
+        type Value = Array;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "struct ArrayStruct")
+        }
+
+        fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+        where
+            V: SeqAccess<'de>,
+        {
+            let version: u16 = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 4 elements, found fewer"))?;
+            check_version(version)?;
+            let dtype: De<DType> = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 4 elements, found fewer"))?;
+            let dim: De<Dim4> = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 4 elements, found fewer"))?;
+            check_size(&dim.0, None).map_err(de::Error::custom)?;
+            if dim.0.elements() == 0 {
+                return empty_array(dtype.0, dim.0).map_err(de::Error::custom);
+            }
+            let data: String = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 4 elements, found fewer"))?;
+            decode_array(dtype.0, dim.0, &data)
+        }
+
+        fn visit_map<V>(self, mut map: V) -> Result<Self::Value, V::Error>
+        where
+            V: MapAccess<'de>,
+        {
+            expect_key(&mut map, ArrayField::Version)?;
+            check_version(map.next_value()?)?;
+            expect_key(&mut map, ArrayField::Dtype)?;
+            let dtype: De<DType> = map.next_value()?;
+            expect_key(&mut map, ArrayField::Dims)?;
+            let dim: De<Dim4> = map.next_value()?;
+            check_size(&dim.0, None).map_err(de::Error::custom)?;
+            if dim.0.elements() == 0 {
+                return empty_array(dtype.0, dim.0).map_err(de::Error::custom);
+            }
+            expect_key(&mut map, ArrayField::Data)?;
+            let data: String = map.next_value()?;
+            decode_array(dtype.0, dim.0, &data)
+        }
+    }
+
+    deserializer.deserialize_struct("Array", ARRAY_FIELDS, Base64Visitor)
+}
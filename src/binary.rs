@@ -0,0 +1,391 @@
+//! Standalone binary `Array` format for `std::io` streams.
+//!
+//! The layout is independent of any serde format. All integers are little-endian:
+//!
+//! | field    | bytes | content                                                       |
+//! |----------|-------|---------------------------------------------------------------|
+//! | magic    | 4     | `b"AFSD"`                                                     |
+//! | version  | 2     | format version, `u16`                                         |
+//! | flags    | 1     | bit 0 set when a checksum follows the data                    |
+//! | dtype    | 1     | `DType` tag, numbered as in `Ser<DType>`                      |
+//! | dims     | 32    | four `u64` dimensions                                         |
+//! | data     | *     | column-major little-endian elements, as in `serialize_packed` |
+//! | checksum | 0 / 4 | CRC32 of `data`, `u32`                                        |
+//!
+//! The data is little-endian regardless of the host: big-endian hosts swap the
+//! bytes of each number while writing and reading. The byte order is thus part
+//! of the format rather than a property of the writer, which is why the header
+//! has no endianness byte, and files move freely between hosts.
+//!
+//! `read_array` checks the magic, version and flags before reading any data,
+//! failing with `Error::BadMagic` on input in another format and with
+//! `Error::Format` on an unknown version or flag.
+//!
+//! The functions work on any `Read` and `Write`. With the `zstd` feature,
+//! `serialize_compressed` and `deserialize_compressed` wrap the format in a
+//! zstd stream. Enable the checksum to detect a truncated or corrupted stream.
+
+use arrayfire::{Array, DType, Dim4, Seq};
+use crc32fast::Hasher;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use super::packed::{
+    build_array, data_len, dtype_size, pack_bits, swap_little_endian, unpack_bits, with_host_bytes,
+};
+use super::{
+    array_dtype, backend_call, check_version, dtype_from_tag, dtype_to_tag, element_count, Error,
+    FORMAT_VERSION,
+};
+
+const MAGIC: &[u8; 4] = b"AFSD";
+
+const FLAG_CHECKSUM: u8 = 1;
+
+/// Length in bytes of the fields before the data.
+pub(crate) const HEADER_LEN: usize = 40;
+
+/// All flags known to this version of the format.
+const KNOWN_FLAGS: u8 = FLAG_CHECKSUM;
+
+/// Upper bound on the host memory used per chunk by `write_array`.
+const CHUNK_BYTES: usize = 4 << 20;
+
+/// Writes `array` to `w` in the binary array format, without a checksum.
+///
+/// The data is copied to the host in chunks of a few megabytes, so the
+/// full array is never held in host memory at once.
+///
+/// ```rust,no_run
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// use arrayfire::{Array, Dim4};
+/// use std::fs::File;
+///
+/// # fn main() {
+/// let array = Array::new(&[1.0f32, 2.0, 3.0], Dim4::new(&[3, 1, 1, 1]));
+/// let mut file = File::create("array.afsd").unwrap();
+/// arrayfire_serde::write_array(&mut file, &array).unwrap();
+///
+/// let mut file = File::open("array.afsd").unwrap();
+/// let array = arrayfire_serde::read_array(&mut file).unwrap();
+/// # }
+/// ```
+pub fn write_array<W: Write>(w: &mut W, array: &Array) -> io::Result<()> {
+    write_array_with_checksum(w, array, false)
+}
+
+/// Writes `array` to `w` in the binary array format, followed by a CRC32
+/// of the data when `checksum` is set.
+///
+/// `read_array` verifies the checksum if present and fails with
+/// `Error::ChecksumMismatch` on corrupted data.
+pub fn write_array_with_checksum<W: Write>(
+    w: &mut W,
+    array: &Array,
+    checksum: bool,
+) -> io::Result<()> {
+    let dtype = array_dtype(array).map_err(invalid_input)?;
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(MAGIC);
+    header.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    header.push(if checksum { FLAG_CHECKSUM } else { 0 });
+    header.push(dtype_to_tag(dtype));
+    for &d in array.dims().get() {
+        header.extend_from_slice(&d.to_le_bytes());
+    }
+    w.write_all(&header)?;
+
+    let mut hasher = Hasher::new();
+    write_data(w, array, dtype, &mut hasher)?;
+    if checksum {
+        w.write_all(&hasher.finalize().to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Writes the element data of `array` to `w` in chunks of at most `CHUNK_BYTES`
+/// host memory, so large arrays never need a full size host buffer.
+fn write_data<W: Write>(
+    w: &mut W,
+    array: &Array,
+    dtype: DType,
+    hasher: &mut Hasher,
+) -> io::Result<()> {
+    let mut write_chunk = |chunk: &Array| {
+        with_host_bytes(chunk, |bytes| {
+            let swapped;
+            let bytes = if cfg!(target_endian = "big") {
+                let mut copy = bytes.to_vec();
+                swap_little_endian(&mut copy, dtype);
+                swapped = copy;
+                &swapped[..]
+            } else {
+                bytes
+            };
+            hasher.update(bytes);
+            w.write_all(bytes)
+        })
+        .map_err(invalid_input)?
+    };
+
+    let elements = array.elements();
+    // sized by the host element, which is a whole byte even for `B8`; a
+    // multiple of 8 keeps bit packed `B8` chunks byte aligned
+    let chunk_elements = (CHUNK_BYTES / (dtype_size(dtype) * 8)).max(1) * 8;
+    if elements <= chunk_elements {
+        return write_chunk(array);
+    }
+
+    let flat = backend_call(|| arrayfire::flat(array)).map_err(invalid_input)?;
+    let mut start = 0;
+    while start < elements {
+        let end = (start + chunk_elements).min(elements);
+        let seq = Seq::new(start as f64, (end - 1) as f64, 1.0);
+        let chunk = backend_call(|| arrayfire::index(&flat, &[seq])).map_err(invalid_input)?;
+        write_chunk(&chunk)?;
+        start = end;
+    }
+    Ok(())
+}
+
+fn window_overflow() -> Error {
+    Error::Format("window offset overflows u64".to_string())
+}
+
+pub(crate) fn invalid_input(err: Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, err.to_string())
+}
+
+/// The fixed size prefix of the binary array format.
+struct Header {
+    flags: u8,
+    dtype: DType,
+    dim: Dim4,
+}
+
+fn read_header<R: Read>(r: &mut R) -> Result<Header, Error> {
+    let mut magic = [0u8; 4];
+    r.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(Error::BadMagic { found: magic });
+    }
+
+    let mut version = [0u8; 2];
+    r.read_exact(&mut version)?;
+    check_version::<Error>(u16::from_le_bytes(version))?;
+
+    let mut flags = [0u8; 1];
+    r.read_exact(&mut flags)?;
+    if flags[0] & !KNOWN_FLAGS != 0 {
+        return Err(Error::Format(format!("unknown flags {:#04x}", flags[0])));
+    }
+
+    let mut dtype = [0u8; 1];
+    r.read_exact(&mut dtype)?;
+    let dtype = dtype_from_tag(u64::from(dtype[0]))?;
+
+    let mut dims = [0u64; 4];
+    for d in dims.iter_mut() {
+        let mut buf = [0u8; 8];
+        r.read_exact(&mut buf)?;
+        *d = u64::from_le_bytes(buf);
+    }
+
+    Ok(Header {
+        flags: flags[0],
+        dtype,
+        dim: Dim4::new(&dims),
+    })
+}
+
+/// Reads the checksum following `data` from `r` and compares it to that of `data`.
+fn verify_checksum<R: Read>(r: &mut R, data: &[u8]) -> Result<(), Error> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf)?;
+    let expected = u32::from_le_bytes(buf);
+    let mut hasher = Hasher::new();
+    hasher.update(data);
+    let found = hasher.finalize();
+    if found != expected {
+        return Err(Error::ChecksumMismatch { expected, found });
+    }
+    Ok(())
+}
+
+/// Reads only the dtype and dimensions of an array written by `write_array`,
+/// leaving `r` positioned at the start of the array data.
+pub fn read_array_header<R: Read>(r: &mut R) -> Result<(DType, Dim4), Error> {
+    read_header(r).map(|header| (header.dtype, header.dim))
+}
+
+/// Reads an array written by `write_array` from `r`.
+pub fn read_array<R: Read>(r: &mut R) -> Result<Array, Error> {
+    let Header { flags, dtype, dim } = read_header(r)?;
+
+    // read through `take` so corrupt dimensions fail on EOF instead of allocating up front
+    let len = data_len(dtype, element_count(&dim)?);
+    let mut bytes = Vec::new();
+    r.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(Error::Io(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "array data is truncated",
+        )));
+    }
+    if flags & FLAG_CHECKSUM != 0 {
+        verify_checksum(r, &bytes)?;
+    }
+    swap_little_endian(&mut bytes, dtype);
+    build_array(dtype, dim, &bytes)
+}
+
+/// Reads an array written by `write_array` from the start of `bytes`, e.g. a
+/// memory-mapped file, and returns it with the number of bytes it took up.
+///
+/// The data is handed to arrayfire straight from `bytes`, without an
+/// intermediate copy on little-endian hosts. Records written back to back can
+/// be read by advancing `bytes` by the returned length.
+///
+/// Malformed input of any kind fails with an `Error` instead of panicking;
+/// the `deserialize` target in `fuzz/` exercises this with `cargo fuzz`.
+///
+/// ```rust
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// use arrayfire::{Array, Dim4};
+///
+/// # fn main() {
+/// let mut bytes = Vec::new();
+/// let array = Array::new(&[1u8, 2, 3], Dim4::new(&[3, 1, 1, 1]));
+/// arrayfire_serde::write_array(&mut bytes, &array).unwrap();
+/// arrayfire_serde::write_array(&mut bytes, &array).unwrap();
+///
+/// let (first, len) = arrayfire_serde::from_slice(&bytes).unwrap();
+/// let (second, _) = arrayfire_serde::from_slice(&bytes[len..]).unwrap();
+/// # }
+/// ```
+pub fn from_slice(bytes: &[u8]) -> Result<(Array, usize), Error> {
+    let mut rest = bytes;
+    let Header { flags, dtype, dim } = read_header(&mut rest)?;
+
+    let len = data_len(dtype, element_count(&dim)?);
+    if rest.len() < len {
+        return Err(Error::Io(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "array data is truncated",
+        )));
+    }
+    let (data, tail) = rest.split_at(len);
+    rest = tail;
+    if flags & FLAG_CHECKSUM != 0 {
+        verify_checksum(&mut rest, data)?;
+    }
+    let array = if cfg!(target_endian = "big") {
+        let mut data = data.to_vec();
+        swap_little_endian(&mut data, dtype);
+        build_array(dtype, dim, &data)?
+    } else {
+        build_array(dtype, dim, data)?
+    };
+    Ok((array, bytes.len() - rest.len()))
+}
+
+/// Reads the sub-array of `extent` elements starting at `offset` from an array
+/// written by `write_array`, without reading the rest of its data.
+///
+/// The window is read as one run of `extent[0]` contiguous elements per
+/// column, seeking over the data in between. A stored checksum is not
+/// verified, since that needs all of the data.
+///
+/// ```rust,no_run
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// use arrayfire::Dim4;
+/// use std::fs::File;
+///
+/// # fn main() {
+/// // the top left 2x2 block of a stored matrix
+/// let mut file = File::open("matrix.afsd").unwrap();
+/// let block = arrayfire_serde::deserialize_window(
+///     Dim4::new(&[0, 0, 0, 0]),
+///     Dim4::new(&[2, 2, 1, 1]),
+///     &mut file,
+/// )
+/// .unwrap();
+/// # }
+/// ```
+pub fn deserialize_window<R: Read + Seek>(
+    offset: Dim4,
+    extent: Dim4,
+    r: &mut R,
+) -> Result<Array, Error> {
+    let Header { dtype, dim, .. } = read_header(r)?;
+    // bounds the element offsets computed below
+    element_count(&dim)?;
+    let dims = dim.get();
+    for i in 0..4 {
+        match offset[i].checked_add(extent[i]) {
+            Some(end) if end <= dims[i] => {}
+            _ => {
+                return Err(Error::Format(format!(
+                    "window at {:?} of {:?} exceeds array dims {:?}",
+                    offset.get(),
+                    extent.get(),
+                    dims
+                )))
+            }
+        }
+    }
+    let elements = element_count(&extent)?;
+    if elements == 0 {
+        return build_array(dtype, extent, &[]);
+    }
+
+    let data_start = r.stream_position()?;
+    let run = extent[0] as usize;
+    let mut bytes = Vec::with_capacity(data_len(dtype, elements));
+    let mut bits = Vec::new();
+    for i3 in 0..extent[3] {
+        for i2 in 0..extent[2] {
+            for i1 in 0..extent[1] {
+                let start = (offset[3] + i3)
+                    .checked_mul(dims[2])
+                    .and_then(|c| c.checked_add(offset[2] + i2))
+                    .and_then(|c| c.checked_mul(dims[1]))
+                    .and_then(|c| c.checked_add(offset[1] + i1))
+                    .and_then(|column| column.checked_mul(dims[0]))
+                    .and_then(|s| s.checked_add(offset[0]))
+                    .ok_or_else(window_overflow)?;
+                if dtype == DType::B8 {
+                    // bit packed: read the bytes covering the run and drop the leading bits
+                    let skip = (start % 8) as usize;
+                    let pos = data_start
+                        .checked_add(start / 8)
+                        .ok_or_else(window_overflow)?;
+                    r.seek(SeekFrom::Start(pos))?;
+                    let mut run_bytes = vec![0u8; (skip + run).div_ceil(8)];
+                    r.read_exact(&mut run_bytes)?;
+                    bits.extend_from_slice(&unpack_bits(&run_bytes, skip + run)[skip..]);
+                } else {
+                    let size = dtype_size(dtype);
+                    let pos = start
+                        .checked_mul(size as u64)
+                        .and_then(|pos| pos.checked_add(data_start))
+                        .ok_or_else(window_overflow)?;
+                    r.seek(SeekFrom::Start(pos))?;
+                    let len = bytes.len();
+                    bytes.resize(len + run * size, 0);
+                    r.read_exact(&mut bytes[len..])?;
+                }
+            }
+        }
+    }
+    if dtype == DType::B8 {
+        bytes = pack_bits(&bits);
+    }
+    swap_little_endian(&mut bytes, dtype);
+    build_array(dtype, extent, &bytes)
+}
@@ -0,0 +1,44 @@
+//! Serialization of boxed `arrayfire` types.
+//!
+//! Intended for use with the `serde` attribute `#[serde(with = "arrayfire_serde::boxed")]`
+//! on `Box` fields, for example a `Box<arrayfire::Array>`:
+//!
+//! ```rust
+//! #[macro_use]
+//! extern crate serde_derive;
+//! extern crate serde;
+//! extern crate arrayfire;
+//! extern crate arrayfire_serde;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct MyStruct {
+//!     #[serde(with = "arrayfire_serde::boxed")]
+//!     tensor: Box<arrayfire::Array>,
+//! }
+//! # fn main() {}
+//! ```
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use alloc::boxed::Box;
+
+use super::{De, Ser};
+
+/// Serializes the boxed value in the same form as `arrayfire_serde::serialize`.
+// serde hands `with` functions a reference to the field, so `&Box<T>` is needed
+#[allow(clippy::borrowed_box)]
+pub fn serialize<T, S>(value: &Box<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    for<'a> Ser<'a, T>: Serialize,
+    S: Serializer,
+{
+    Ser::new(&**value).serialize(serializer)
+}
+
+/// Deserializes a value written by `serialize` into a `Box`.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Box<T>, D::Error>
+where
+    De<T>: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    De::<T>::deserialize(deserializer).map(|value| Box::new(value.into_inner()))
+}
@@ -0,0 +1,166 @@
+//! Buffering of element data read before the dtype and dims of an `Array`.
+
+use serde::de::value::SeqDeserializer;
+use serde::de::{self, DeserializeSeed, IntoDeserializer, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+
+/// A single element as found in a self-describing format, not yet typed.
+///
+/// Complex elements are `(re, im)` pairs and buffered as a `Seq`.
+pub(crate) enum Element {
+    Bool(bool),
+    U64(u64),
+    I64(i64),
+    F64(f64),
+    Str(String),
+    Seq(Vec<Element>),
+}
+
+impl<'de> Deserialize<'de> for Element {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ElementVisitor;
+
+        impl<'de> Visitor<'de> for ElementVisitor {
+            type Value = Element;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "an array element")
+            }
+
+            fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E> {
+                Ok(Element::Bool(value))
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(Element::U64(value))
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
+                Ok(Element::I64(value))
+            }
+
+            fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E> {
+                Ok(Element::F64(value))
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+                Ok(Element::Str(value.into()))
+            }
+
+            fn visit_string<E>(self, value: String) -> Result<Self::Value, E> {
+                Ok(Element::Str(value))
+            }
+
+            fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+            where
+                V: SeqAccess<'de>,
+            {
+                let mut elements = Vec::new();
+                while let Some(element) = seq.next_element()? {
+                    elements.push(element);
+                }
+                Ok(Element::Seq(elements))
+            }
+        }
+
+        deserializer.deserialize_any(ElementVisitor)
+    }
+}
+
+/// Replays a buffered `Element` to a visitor.
+pub(crate) struct ElementDeserializer<E> {
+    element: Element,
+    marker: PhantomData<E>,
+}
+
+impl<'de, E: de::Error> IntoDeserializer<'de, E> for Element {
+    type Deserializer = ElementDeserializer<E>;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        ElementDeserializer {
+            element: self,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, E: de::Error> Deserializer<'de> for ElementDeserializer<E> {
+    type Error = E;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, E>
+    where
+        V: Visitor<'de>,
+    {
+        match self.element {
+            Element::Bool(value) => visitor.visit_bool(value),
+            Element::U64(value) => visitor.visit_u64(value),
+            Element::I64(value) => visitor.visit_i64(value),
+            Element::F64(value) => visitor.visit_f64(value),
+            Element::Str(value) => visitor.visit_string(value),
+            Element::Seq(elements) => {
+                let mut seq = SeqDeserializer::new(elements.into_iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Seed buffering the element data of an `Array`, failing once it holds
+/// more than `max_elements` elements.
+pub(crate) struct BufferSeed {
+    pub(crate) max_elements: Option<u64>,
+}
+
+impl<'de> Visitor<'de> for BufferSeed {
+    type Value = Vec<Element>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a sequence of array elements")
+    }
+
+    fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+    where
+        V: SeqAccess<'de>,
+    {
+        let mut elements = Vec::new();
+        while let Some(element) = seq.next_element()? {
+            elements.push(element);
+            match self.max_elements {
+                Some(max) if elements.len() as u64 > max => {
+                    return Err(de::Error::custom(format_args!(
+                        "array data exceeds the limit of {} elements",
+                        max
+                    )))
+                }
+                _ => {}
+            }
+        }
+        Ok(elements)
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for BufferSeed {
+    type Value = Vec<Element>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
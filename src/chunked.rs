@@ -0,0 +1,111 @@
+//! `Array` serialization that copies the element data to the host in chunks.
+
+use arrayfire::{Array, DType, HasAfEnum, Seq};
+use num::Complex;
+use serde::ser::{self, SerializeSeq};
+use serde::{Serialize, Serializer};
+
+use super::{backend_call, get_data, serialize_array, DataEncoding};
+
+/// Serializes an `Array` in the same form as `serialize`, copying its data to
+/// the host at most `chunk` elements at a time.
+///
+/// The elements are written as one serde sequence, so a streaming serializer,
+/// e.g. `serde_json` writing to a socket, never needs a host copy of the whole
+/// array. With the `zerocopy` feature, formats that are not human readable
+/// get the data as bytes like `serialize` writes it, which copies the whole
+/// array to the host at once, so that `deserialize` can read it back.
+///
+/// ```rust
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+/// extern crate serde_json;
+///
+/// use arrayfire::{Array, Dim4};
+/// use arrayfire_serde::ChunkedArray;
+///
+/// # fn main() {
+/// let array = Array::new(&[1.0f32, 2.0, 3.0], Dim4::new(&[3, 1, 1, 1]));
+/// let json = serde_json::to_string(&ChunkedArray(&array, 2)).unwrap();
+/// # }
+/// ```
+pub struct ChunkedArray<'a>(pub &'a Array, pub usize);
+
+impl<'a> Serialize for ChunkedArray<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_array(self.0, serializer, DataEncoding::Chunked(self.1))
+    }
+}
+
+/// Serializes the elements of `chunk`, mapped by `f`, into `seq`.
+fn serialize_elements<T, U, M>(chunk: &Array, seq: &mut M, f: fn(T) -> U) -> Result<(), M::Error>
+where
+    T: HasAfEnum + Clone + Default,
+    U: Serialize,
+    M: SerializeSeq,
+{
+    for value in get_data::<T>(chunk).map_err(ser::Error::custom)? {
+        seq.serialize_element(&f(value))?;
+    }
+    Ok(())
+}
+
+fn serialize_chunk<M: SerializeSeq>(
+    chunk: &Array,
+    dtype: DType,
+    seq: &mut M,
+) -> Result<(), M::Error> {
+    fn complex<T>(c: Complex<T>) -> (T, T) {
+        (c.re, c.im)
+    }
+
+    match dtype {
+        DType::F32 => serialize_elements::<f32, _, M>(chunk, seq, |v| v),
+        DType::F64 => serialize_elements::<f64, _, M>(chunk, seq, |v| v),
+        DType::S16 => serialize_elements::<i16, _, M>(chunk, seq, |v| v),
+        DType::S32 => serialize_elements::<i32, _, M>(chunk, seq, |v| v),
+        DType::S64 => serialize_elements::<i64, _, M>(chunk, seq, |v| v),
+        DType::U8 => serialize_elements::<u8, _, M>(chunk, seq, |v| v),
+        DType::U16 => serialize_elements::<u16, _, M>(chunk, seq, |v| v),
+        DType::U32 => serialize_elements::<u32, _, M>(chunk, seq, |v| v),
+        DType::U64 => serialize_elements::<u64, _, M>(chunk, seq, |v| v),
+        DType::B8 => serialize_elements::<bool, _, M>(chunk, seq, |v| v),
+        DType::C32 => serialize_elements::<Complex<f32>, _, M>(chunk, seq, complex),
+        DType::C64 => serialize_elements::<Complex<f64>, _, M>(chunk, seq, complex),
+    }
+}
+
+/// Serializes the element data of `array` as a sequence, copying at most
+/// `chunk` elements to the host at a time.
+pub(crate) fn serialize_data<S>(
+    array: &Array,
+    dtype: DType,
+    chunk: usize,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let elements = array.elements();
+    let chunk = chunk.max(1);
+    let mut seq = serializer.serialize_seq(Some(elements))?;
+    if elements <= chunk {
+        serialize_chunk(array, dtype, &mut seq)?;
+        return seq.end();
+    }
+
+    let flat = backend_call(|| arrayfire::flat(array)).map_err(ser::Error::custom)?;
+    let mut start = 0;
+    while start < elements {
+        let end = (start + chunk).min(elements);
+        let range = Seq::new(start as f64, (end - 1) as f64, 1.0);
+        let part =
+            backend_call(|| arrayfire::index(&flat, &[range])).map_err(ser::Error::custom)?;
+        serialize_chunk(&part, dtype, &mut seq)?;
+        start = end;
+    }
+    seq.end()
+}
@@ -0,0 +1,120 @@
+//! Comparison of whole arrays.
+
+use arrayfire::{Array, DType, HasAfEnum};
+use num::{Complex, Float};
+
+use super::packed::with_host_bytes;
+use super::{array_dtype, get_data, Error};
+
+/// Returns whether `a` and `b` have the same dtype, dims and element data.
+///
+/// Elements are compared by their bit patterns: exact for integers and `B8`,
+/// and bit-exact for floating point and complex types, so a `NaN` equals the
+/// same `NaN` while `0.0` and `-0.0` differ. Arrays that cannot be read back
+/// from the backend compare unequal. `arrays_approx_equal` compares float and
+/// complex elements with a tolerance instead.
+///
+/// ```rust
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// use arrayfire::{Array, Dim4};
+///
+/// # fn main() {
+/// let dim = Dim4::new(&[2, 1, 1, 1]);
+/// let a = Array::new(&[1.0f32, 2.0], dim);
+/// let b = Array::new(&[1.0f32, 2.0], dim);
+/// assert!(arrayfire_serde::arrays_equal(&a, &b));
+/// # }
+/// ```
+pub fn arrays_equal(a: &Array, b: &Array) -> bool {
+    match (array_dtype(a), array_dtype(b)) {
+        (Ok(dtype_a), Ok(dtype_b)) if dtype_a == dtype_b => {}
+        _ => return false,
+    }
+    if a.dims() != b.dims() {
+        return false;
+    }
+    let equal = with_host_bytes(a, |bytes_a| {
+        with_host_bytes(b, |bytes_b| bytes_a == bytes_b)
+    });
+    matches!(equal, Ok(Ok(true)))
+}
+
+/// Returns whether `a` and `b` have the same dtype and dims and their float
+/// elements differ by at most `tolerance`.
+///
+/// `F32` and `F64` elements are compared by value, `C32` and `C64` elements
+/// by their real and imaginary parts separately. `NaN` matches `NaN` and an
+/// infinity matches only the same infinity, so arrays that are
+/// `arrays_equal` are always approximately equal. Other dtypes are compared
+/// exactly like `arrays_equal`, whatever the tolerance.
+///
+/// ```rust
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+/// extern crate num;
+///
+/// use arrayfire::{Array, Dim4};
+/// use num::Complex;
+///
+/// # fn main() {
+/// let dim = Dim4::new(&[1, 1, 1, 1]);
+/// let a = Array::new(&[Complex::new(1.0f32, 2.0)], dim);
+/// let b = Array::new(&[Complex::new(1.0f32, 2.0001)], dim);
+/// assert!(arrayfire_serde::arrays_approx_equal(&a, &b, 1e-3));
+/// assert!(!arrayfire_serde::arrays_approx_equal(&a, &b, 1e-6));
+/// # }
+/// ```
+pub fn arrays_approx_equal(a: &Array, b: &Array, tolerance: f64) -> bool {
+    let dtype = match (array_dtype(a), array_dtype(b)) {
+        (Ok(dtype_a), Ok(dtype_b)) if dtype_a == dtype_b => dtype_a,
+        _ => return false,
+    };
+    if a.dims() != b.dims() {
+        return false;
+    }
+    let equal = match dtype {
+        DType::F32 => floats_close::<f32>(a, b, tolerance),
+        DType::F64 => floats_close::<f64>(a, b, tolerance),
+        DType::C32 => complex_close::<f32>(a, b, tolerance),
+        DType::C64 => complex_close::<f64>(a, b, tolerance),
+        _ => return arrays_equal(a, b),
+    };
+    equal.unwrap_or(false)
+}
+
+fn close<T: Float>(x: T, y: T, tolerance: f64) -> bool {
+    if x.is_nan() || y.is_nan() {
+        return x.is_nan() && y.is_nan();
+    }
+    if x.is_infinite() || y.is_infinite() {
+        return x == y;
+    }
+    (x - y).abs().to_f64().is_some_and(|d| d <= tolerance)
+}
+
+fn floats_close<T>(a: &Array, b: &Array, tolerance: f64) -> Result<bool, Error>
+where
+    T: Float + HasAfEnum + Default,
+{
+    if a.elements() == 0 && b.elements() == 0 && a.dims() == b.dims() {
+        return Ok(true);
+    }
+    let (a, b) = (get_data::<T>(a)?, get_data::<T>(b)?);
+    Ok(a.iter().zip(&b).all(|(&x, &y)| close(x, y, tolerance)))
+}
+
+fn complex_close<T>(a: &Array, b: &Array, tolerance: f64) -> Result<bool, Error>
+where
+    T: Float + Default,
+    Complex<T>: HasAfEnum,
+{
+    if a.elements() == 0 && b.elements() == 0 && a.dims() == b.dims() {
+        return Ok(true);
+    }
+    let (a, b) = (get_data::<Complex<T>>(a)?, get_data::<Complex<T>>(b)?);
+    Ok(a.iter()
+        .zip(&b)
+        .all(|(x, y)| close(x.re, y.re, tolerance) && close(x.im, y.im, tolerance)))
+}
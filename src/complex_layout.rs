@@ -0,0 +1,270 @@
+//! `Array` representation with a selectable layout of complex element data.
+
+use arrayfire::{Array, DType, Dim4, HasAfEnum};
+use num::Complex;
+use serde::de::{self, DeserializeSeed, SeqAccess, Visitor};
+use serde::ser::{self, SerializeTuple};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::{
+    array_dtype, backend_call, check_length, check_size, check_version, empty_array, get_data,
+    new_array, ArrayData, ArrayDataSeed, DataEncoding, De, Ser, FORMAT_VERSION,
+};
+
+/// Layout of the real and imaginary parts of serialized complex data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ComplexLayout {
+    /// `[re0, im0, re1, im1, ...]`
+    Interleaved,
+    /// `[re0, re1, ..., im0, im1, ...]`
+    Planar,
+}
+
+impl Serialize for ComplexLayout {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u8(match *self {
+            ComplexLayout::Interleaved => 0,
+            ComplexLayout::Planar => 1,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for ComplexLayout {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match u8::deserialize(deserializer)? {
+            0 => Ok(ComplexLayout::Interleaved),
+            1 => Ok(ComplexLayout::Planar),
+            value => Err(de::Error::custom(format!(
+                "invalid complex layout {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Private helper serializing complex element data as a flat sequence of parts.
+struct ComplexData<'a> {
+    array: &'a Array,
+    dtype: DType,
+    layout: ComplexLayout,
+}
+
+impl<'a> Serialize for ComplexData<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        fn serialize_parts<T, S>(
+            array: &Array,
+            layout: ComplexLayout,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error>
+        where
+            T: Copy + Default + Serialize,
+            Complex<T>: HasAfEnum,
+            S: Serializer,
+        {
+            let data = get_data::<Complex<T>>(array).map_err(ser::Error::custom)?;
+            let mut parts = Vec::with_capacity(data.len() * 2);
+            match layout {
+                ComplexLayout::Interleaved => {
+                    for c in &data {
+                        parts.push(c.re);
+                        parts.push(c.im);
+                    }
+                }
+                ComplexLayout::Planar => {
+                    parts.extend(data.iter().map(|c| c.re));
+                    parts.extend(data.iter().map(|c| c.im));
+                }
+            }
+            parts.serialize(serializer)
+        }
+
+        match self.dtype {
+            DType::C32 => serialize_parts::<f32, S>(self.array, self.layout, serializer),
+            DType::C64 => serialize_parts::<f64, S>(self.array, self.layout, serializer),
+            _ => ArrayData {
+                array: self.array,
+                encoding: DataEncoding::Plain,
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
+/// Private seed reading the element data written by `ComplexData`.
+struct ComplexDataSeed {
+    dtype: DType,
+    dim: Dim4,
+    layout: ComplexLayout,
+}
+
+impl<'de> DeserializeSeed<'de> for ComplexDataSeed {
+    type Value = Array;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        fn get_parts<'de, T, D>(
+            deserializer: D,
+            dim: &Dim4,
+            layout: ComplexLayout,
+        ) -> Result<Array, D::Error>
+        where
+            T: Copy + Deserialize<'de>,
+            Complex<T>: HasAfEnum,
+            D: Deserializer<'de>,
+        {
+            let parts = Vec::<T>::deserialize(deserializer)?;
+            if parts.len() % 2 != 0 {
+                return Err(de::Error::custom(format_args!(
+                    "complex data holds an odd number of parts {}",
+                    parts.len()
+                )));
+            }
+            let elements = parts.len() / 2;
+            check_length::<D::Error>(elements, dim)?;
+            let data: Vec<Complex<T>> = match layout {
+                ComplexLayout::Interleaved => parts
+                    .chunks(2)
+                    .map(|c| Complex { re: c[0], im: c[1] })
+                    .collect(),
+                ComplexLayout::Planar => {
+                    let (re, im) = parts.split_at(elements);
+                    re.iter()
+                        .zip(im)
+                        .map(|(&re, &im)| Complex { re, im })
+                        .collect()
+                }
+            };
+            new_array(&data, *dim).map_err(de::Error::custom)
+        }
+
+        match self.dtype {
+            DType::C32 => get_parts::<f32, D>(deserializer, &self.dim, self.layout),
+            DType::C64 => get_parts::<f64, D>(deserializer, &self.dim, self.layout),
+            _ => ArrayDataSeed {
+                dtype: self.dtype,
+                dim: self.dim,
+                encoding: DataEncoding::Plain,
+            }
+            .deserialize(deserializer),
+        }
+    }
+}
+
+/// Serializes an `Array` with complex data in the given layout.
+///
+/// The representation is a `(version, dtype, dims, layout, data)` tuple. For
+/// `C32` and `C64` arrays `data` is a flat sequence of the real and imaginary
+/// parts, either interleaved or as all real parts followed by all imaginary
+/// parts. Data of other dtypes is written as by `serialize`. Use
+/// `deserialize_complex` to read the array back.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate serde;
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// use arrayfire_serde::ComplexLayout;
+///
+/// fn planar<S: serde::Serializer>(
+///     array: &arrayfire::Array,
+///     serializer: S,
+/// ) -> Result<S::Ok, S::Error> {
+///     arrayfire_serde::serialize_complex(ComplexLayout::Planar, array, serializer)
+/// }
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Spectrum {
+///     #[serde(serialize_with = "planar")]
+///     #[serde(deserialize_with = "arrayfire_serde::deserialize_complex")]
+///     bins: arrayfire::Array,
+/// }
+/// # fn main() {}
+/// ```
+pub fn serialize_complex<S>(
+    layout: ComplexLayout,
+    array: &Array,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    backend_call(|| array.eval()).map_err(ser::Error::custom)?;
+    let dim = array.dims();
+    let dtype = array_dtype(array).map_err(ser::Error::custom)?;
+    let empty = dim.elements() == 0;
+
+    let mut tup = serializer.serialize_tuple(if empty { 4 } else { 5 })?;
+    tup.serialize_element(&FORMAT_VERSION)?;
+    tup.serialize_element(&Ser::new(&dtype))?;
+    tup.serialize_element(&Ser::new(&dim))?;
+    tup.serialize_element(&layout)?;
+    if !empty {
+        tup.serialize_element(&ComplexData {
+            array,
+            dtype,
+            layout,
+        })?;
+    }
+    tup.end()
+}
+
+/// Deserializes an `Array` written by `serialize_complex` in either layout.
+pub fn deserialize_complex<'de, D>(deserializer: D) -> Result<Array, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct ComplexVisitor;
+
+    impl<'de> Visitor<'de> for ComplexVisitor {
+        type Value = Array;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "tuple as a seq of 5 elements")
+        }
+
+        fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+        where
+            V: SeqAccess<'de>,
+        {
+            let version: u16 = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 5 elements, found fewer"))?;
+            check_version(version)?;
+            let dtype: De<DType> = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 5 elements, found fewer"))?;
+            let dim: De<Dim4> = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 5 elements, found fewer"))?;
+            let layout: ComplexLayout = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 5 elements, found fewer"))?;
+            let (dtype, dim) = (dtype.0, dim.0);
+            check_size(&dim, None).map_err(de::Error::custom)?;
+            if dim.elements() == 0 {
+                return empty_array(dtype, dim).map_err(de::Error::custom);
+            }
+
+            let seed = ComplexDataSeed { dtype, dim, layout };
+            seq.next_element_seed(seed)?
+                .ok_or_else(|| de::Error::custom("expected 5 elements, found fewer"))
+        }
+    }
+
+    deserializer.deserialize_tuple(5, ComplexVisitor)
+}
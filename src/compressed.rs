@@ -0,0 +1,97 @@
+//! The binary array format wrapped in a zstd stream.
+//!
+//! | field  | bytes | content                                               |
+//! |--------|-------|-------------------------------------------------------|
+//! | magic  | 4     | `b"AFSZ"`                                             |
+//! | length | 8     | uncompressed length in bytes, `u64`, little-endian    |
+//! | stream | *     | zstd stream of the array in the `write_array` format  |
+//!
+//! The recorded length lets `deserialize_compressed` tell a stream that was
+//! cut short from a complete one.
+
+use arrayfire::Array;
+use std::io::{self, Read, Write};
+
+use super::binary::{from_slice, invalid_input, write_array, HEADER_LEN};
+use super::packed::data_len;
+use super::{array_dtype, Error};
+
+const MAGIC: &[u8; 4] = b"AFSZ";
+
+/// Writes `array` to `w` in the binary array format of `write_array`,
+/// compressed with zstd at `level`.
+///
+/// The data is compressed as it is copied to the host, so neither the full
+/// uncompressed nor the full compressed array is held in memory at once.
+///
+/// ```rust,no_run
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// use arrayfire::{Array, Dim4};
+/// use std::fs::File;
+///
+/// # fn main() {
+/// let array = Array::new(&[1.0f32, 2.0, 3.0], Dim4::new(&[3, 1, 1, 1]));
+/// let mut file = File::create("array.afsz").unwrap();
+/// arrayfire_serde::serialize_compressed(&mut file, &array, 3).unwrap();
+///
+/// let mut file = File::open("array.afsz").unwrap();
+/// let array = arrayfire_serde::deserialize_compressed(&mut file).unwrap();
+/// # }
+/// ```
+pub fn serialize_compressed<W: Write>(w: &mut W, array: &Array, level: i32) -> io::Result<()> {
+    let dtype = array_dtype(array).map_err(invalid_input)?;
+    let len = HEADER_LEN + data_len(dtype, array.elements());
+    w.write_all(MAGIC)?;
+    w.write_all(&(len as u64).to_le_bytes())?;
+
+    let mut encoder = zstd::Encoder::new(w, level)?;
+    write_array(&mut encoder, array)?;
+    encoder.finish()?;
+    Ok(())
+}
+
+/// Reads an array written by `serialize_compressed` from `r`.
+///
+/// Fails with `Error::BadMagic` on input in another format, and with an
+/// `Error::Io` of kind `UnexpectedEof` when the compressed stream ends before
+/// the recorded length has been decompressed.
+pub fn deserialize_compressed<R: Read>(r: &mut R) -> Result<Array, Error> {
+    let mut magic = [0u8; 4];
+    r.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(Error::BadMagic { found: magic });
+    }
+    let mut len = [0u8; 8];
+    r.read_exact(&mut len)?;
+    let len = u64::from_le_bytes(len);
+
+    // read through `take` so a corrupt length fails on EOF instead of allocating up front
+    let decoder = zstd::Decoder::new(r)?;
+    let mut bytes = Vec::new();
+    match decoder.take(len).read_to_end(&mut bytes) {
+        Ok(_) => {}
+        Err(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => {}
+        Err(err) => return Err(err.into()),
+    }
+    if bytes.len() as u64 != len {
+        return Err(Error::Io(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!(
+                "compressed array is truncated: expected {} bytes, decompressed {}",
+                len,
+                bytes.len()
+            ),
+        )));
+    }
+
+    let (array, read) = from_slice(&bytes)?;
+    if read != bytes.len() {
+        return Err(Error::Format(format!(
+            "recorded length {} does not match the array length {}",
+            len, read
+        )));
+    }
+    Ok(array)
+}
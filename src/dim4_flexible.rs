@@ -0,0 +1,81 @@
+//! Serialization of `Dim4` accepting 1 to 4 dimensions for use with
+//! `#[serde(with = "arrayfire_serde::dim4_flexible")]`.
+//!
+//! Missing trailing dimensions default to `1`, as with `Dim4::new` in
+//! arrayfire, so `[28, 28]` reads as `[28, 28, 1, 1]`. A `Dim4` is written as
+//! a sequence of all four dimensions, which any format can read back.
+//!
+//! ```rust
+//! #[macro_use]
+//! extern crate serde_derive;
+//! extern crate serde;
+//! extern crate arrayfire;
+//! extern crate arrayfire_serde;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Image {
+//!     #[serde(with = "arrayfire_serde::dim4_flexible")]
+//!     shape: arrayfire::Dim4,
+//! }
+//! # fn main() {}
+//! ```
+
+use arrayfire::Dim4;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::SerializeSeq;
+use serde::{Deserializer, Serializer};
+use core::fmt;
+
+/// Serializes `dim` as a sequence of four `u64` dimensions.
+pub fn serialize<S>(dim: &Dim4, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut seq = serializer.serialize_seq(Some(4))?;
+    for d in dim.get() {
+        seq.serialize_element(d)?;
+    }
+    seq.end()
+}
+
+/// Deserializes a `Dim4` from a sequence of 1 to 4 dimensions, padding
+/// missing trailing dimensions with `1`.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Dim4, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct FlexibleDim4Visitor;
+
+    impl<'de> Visitor<'de> for FlexibleDim4Visitor {
+        type Value = Dim4;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "seq of 1 to 4 dimensions")
+        }
+
+        fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+        where
+            V: SeqAccess<'de>,
+        {
+            let mut dims = [1u64; 4];
+            let mut len = 0;
+            while let Some(d) = seq.next_element()? {
+                if len == dims.len() {
+                    return Err(de::Error::custom(
+                        "expected at most 4 dimensions, found more",
+                    ));
+                }
+                dims[len] = d;
+                len += 1;
+            }
+            if len == 0 {
+                return Err(de::Error::custom(
+                    "expected at least 1 dimension, found none",
+                ));
+            }
+            Ok(Dim4::new(&dims))
+        }
+    }
+
+    deserializer.deserialize_seq(FlexibleDim4Visitor)
+}
@@ -0,0 +1,66 @@
+//! Serialization of `Dim4` as a named struct for use with
+//! `#[serde(with = "arrayfire_serde::dim4_named")]`.
+//!
+//! Human readable formats get `{ "d0": .., "d1": .., "d2": .., "d3": .. }`,
+//! other formats keep the 4-tuple form of `arrayfire_serde::serialize`.
+//!
+//! ```rust
+//! #[macro_use]
+//! extern crate serde_derive;
+//! extern crate serde;
+//! extern crate arrayfire;
+//! extern crate arrayfire_serde;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Layer {
+//!     #[serde(with = "arrayfire_serde::dim4_named")]
+//!     shape: arrayfire::Dim4,
+//! }
+//! # fn main() {}
+//! ```
+
+use arrayfire::Dim4;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{De, Ser};
+
+/// Private helper for the named form of a `Dim4`.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Dim4", deny_unknown_fields)]
+struct NamedDim4 {
+    d0: u64,
+    d1: u64,
+    d2: u64,
+    d3: u64,
+}
+
+/// Serializes `dim` as a `Dim4` struct with fields `d0` to `d3`, or as a
+/// 4-tuple in formats that are not human readable.
+pub fn serialize<S>(dim: &Dim4, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if !serializer.is_human_readable() {
+        return Ser::new(dim).serialize(serializer);
+    }
+    let dims = dim.get();
+    NamedDim4 {
+        d0: dims[0],
+        d1: dims[1],
+        d2: dims[2],
+        d3: dims[3],
+    }
+    .serialize(serializer)
+}
+
+/// Deserializes a `Dim4` written by `serialize`.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Dim4, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    if !deserializer.is_human_readable() {
+        return De::<Dim4>::deserialize(deserializer).map(De::into_inner);
+    }
+    let named = NamedDim4::deserialize(deserializer)?;
+    Ok(Dim4::new(&[named.d0, named.d1, named.d2, named.d3]))
+}
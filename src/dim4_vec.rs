@@ -0,0 +1,41 @@
+//! Serialization of `Vec<Dim4>` for use with
+//! `#[serde(with = "arrayfire_serde::dim4_vec")]`.
+//!
+//! Each `Dim4` uses the same 4-tuple form as `arrayfire_serde::serialize`.
+//!
+//! ```rust
+//! #[macro_use]
+//! extern crate serde_derive;
+//! extern crate serde;
+//! extern crate arrayfire;
+//! extern crate arrayfire_serde;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Schedule {
+//!     #[serde(with = "arrayfire_serde::dim4_vec")]
+//!     windows: Vec<arrayfire::Dim4>,
+//! }
+//! # fn main() {}
+//! ```
+
+use arrayfire::Dim4;
+use serde::{Deserializer, Serializer};
+use alloc::vec::Vec;
+
+use super::seq;
+
+/// Serializes `dims` as a sequence of `Dim4` tuples.
+pub fn serialize<S>(dims: &[Dim4], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    seq::serialize(dims, serializer)
+}
+
+/// Deserializes a sequence written by `serialize`.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Dim4>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    seq::deserialize(deserializer)
+}
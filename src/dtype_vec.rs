@@ -0,0 +1,43 @@
+//! Serialization of `Vec<DType>` for use with
+//! `#[serde(with = "arrayfire_serde::dtype_vec")]`.
+//!
+//! Each `DType` is written as in `arrayfire_serde::serialize`: its lowercase
+//! name, e.g. `"f32"`, in human-readable formats and its `u8` tag otherwise.
+//!
+//! ```rust
+//! #[macro_use]
+//! extern crate serde_derive;
+//! extern crate serde;
+//! extern crate arrayfire;
+//! extern crate arrayfire_serde;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Schema {
+//!     #[serde(with = "arrayfire_serde::dtype_vec")]
+//!     columns: Vec<arrayfire::DType>,
+//! }
+//! # fn main() {}
+//! ```
+
+use arrayfire::DType;
+use serde::{Deserializer, Serializer};
+use alloc::vec::Vec;
+
+use super::seq;
+
+/// Serializes `dtypes` as a sequence in the form of `Ser<DType>`: tags, or
+/// names in human-readable formats.
+pub fn serialize<S>(dtypes: &[DType], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    seq::serialize(dtypes, serializer)
+}
+
+/// Deserializes a sequence written by `serialize`.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<DType>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    seq::deserialize(deserializer)
+}
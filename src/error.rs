@@ -0,0 +1,115 @@
+use alloc::string::{String, ToString};
+use arrayfire::DType;
+use core::fmt;
+use serde::de;
+#[cfg(feature = "std")]
+use std::io;
+
+use super::layout::Layout;
+
+/// Errors for recoverable failures while converting arrayfire types.
+///
+/// Within `serde` impls these are surfaced through the format's own error type
+/// using their `Display` message.
+#[derive(Debug)]
+pub enum Error {
+    /// The number of data elements does not match the array dimensions.
+    LengthMismatch { expected: usize, found: usize },
+    /// The serialized dtype value is not a known `DType`.
+    InvalidDType(u64),
+    /// The arrayfire backend reported an error.
+    BackendError(String),
+    /// The underlying serde format failed to encode or decode the array.
+    Format(String),
+    /// Reading or writing the binary array format failed.
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    /// The checksum stored with the binary array data does not match the data.
+    ChecksumMismatch { expected: u32, found: u32 },
+    /// The stored dtype differs from the one required by the caller.
+    DTypeMismatch { expected: DType, found: DType },
+    /// The declared array dimensions exceed the allowed number of elements.
+    TooLarge { dims: [u64; 4], max: u64 },
+    /// The deserialized array does not have the layout recorded with it.
+    LayoutMismatch { expected: Layout, found: Layout },
+    /// The array dimensions are not `[height, width, channels, 1]` with 1, 3 or 4 channels.
+    NotAnImage { dims: [u64; 4] },
+    /// The input does not start with the magic bytes of the binary array format.
+    BadMagic { found: [u8; 4] },
+    /// A float or complex array holds `NaN` or infinite values.
+    NonFinite { dtype: DType },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::LengthMismatch { expected, found } => write!(
+                f,
+                "data length mismatch: expected {} elements, found {}",
+                expected, found
+            ),
+            Error::InvalidDType(value) => write!(f, "invalid dtype discriminant {}", value),
+            Error::BackendError(ref msg) => write!(f, "arrayfire backend error: {}", msg),
+            Error::Format(ref msg) => write!(f, "format error: {}", msg),
+            #[cfg(feature = "std")]
+            Error::Io(ref err) => write!(f, "io error: {}", err),
+            Error::ChecksumMismatch { expected, found } => write!(
+                f,
+                "checksum mismatch: expected {:08x}, found {:08x}",
+                expected, found
+            ),
+            Error::DTypeMismatch { expected, found } => write!(
+                f,
+                "dtype mismatch: expected {:?}, found {:?}",
+                expected, found
+            ),
+            Error::TooLarge { dims, max } => write!(
+                f,
+                "array dims {:?} exceed the limit of {} elements",
+                dims, max
+            ),
+            Error::LayoutMismatch { expected, found } => write!(
+                f,
+                "layout mismatch: expected {:?}, found {:?}",
+                expected, found
+            ),
+            Error::NotAnImage { dims } => write!(
+                f,
+                "array dims {:?} are not [height, width, channels, 1] with 1, 3 or 4 channels",
+                dims
+            ),
+            Error::BadMagic { found } => write!(
+                f,
+                "bad magic {:?}: not an arrayfire_serde binary array",
+                found
+            ),
+            Error::NonFinite { dtype } => {
+                write!(f, "{:?} array holds NaN or infinite values", dtype)
+            }
+        }
+    }
+}
+
+/// `de::StdError` is `std::error::Error`, or serde's stand-in without `std`.
+impl de::StdError for Error {
+    #[cfg(feature = "std")]
+    fn source(&self) -> Option<&(dyn de::StdError + 'static)> {
+        match *self {
+            Error::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Error {
+        Error::Format(msg.to_string())
+    }
+}
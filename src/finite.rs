@@ -0,0 +1,56 @@
+//! `Array` serialization that rejects non-finite values.
+
+use arrayfire::{any_true_all, isinf, isnan, Array, DType};
+use serde::ser;
+use serde::{Serialize, Serializer};
+
+use super::{array_dtype, backend_call, Error, Ser};
+
+/// Fails with `Error::NonFinite` if a float or complex `array` holds a NaN or
+/// an infinity. The scan runs on the device, nothing is copied to the host.
+fn check_finite(array: &Array) -> Result<(), Error> {
+    let dtype = array_dtype(array)?;
+    match dtype {
+        DType::F32 | DType::F64 | DType::C32 | DType::C64 => {}
+        _ => return Ok(()),
+    }
+    let non_finite = backend_call(|| {
+        any_true_all(&isnan(array)).0 != 0.0 || any_true_all(&isinf(array)).0 != 0.0
+    })?;
+    if non_finite {
+        return Err(Error::NonFinite { dtype });
+    }
+    Ok(())
+}
+
+/// Serializes an `Array` like `serialize` after checking that it holds no
+/// `NaN` or infinite values, failing with `Error::NonFinite` otherwise.
+///
+/// Intended for persisting model weights, where a non-finite value usually
+/// means a diverged training run that should not overwrite a good checkpoint.
+/// Float and complex arrays are scanned on the device with `isnan`, `isinf`
+/// and `any_true_all` before any data is copied; other dtypes cannot hold
+/// non-finite values and skip the check. The output is read by `deserialize`.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate serde;
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Checkpoint {
+///     #[serde(serialize_with = "arrayfire_serde::serialize_checked_finite")]
+///     #[serde(deserialize_with = "arrayfire_serde::deserialize")]
+///     weights: arrayfire::Array,
+/// }
+/// # fn main() {}
+/// ```
+pub fn serialize_checked_finite<S>(array: &Array, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    check_finite(array).map_err(ser::Error::custom)?;
+    Ser::new(array).serialize(serializer)
+}
@@ -0,0 +1,108 @@
+//! `Array` representation for `U8` image data with a small metadata header.
+
+use arrayfire::{Array, DType};
+use core::fmt;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::{self, SerializeTuple};
+use serde::{Deserializer, Serializer};
+
+use super::{array_dtype, De, Error, Ser};
+
+/// Numbers of color channels accepted in the third dimension.
+const CHANNELS: [u64; 3] = [1, 3, 4];
+
+/// Checks that `array` is a `U8` image of dims `[height, width, channels, 1]`,
+/// returning its `(height, width, channels)`.
+fn check_image(array: &Array) -> Result<(u64, u64, u8), Error> {
+    let dtype = array_dtype(array)?;
+    if dtype != DType::U8 {
+        return Err(Error::DTypeMismatch {
+            expected: DType::U8,
+            found: dtype,
+        });
+    }
+    let dims = *array.dims().get();
+    if !CHANNELS.contains(&dims[2]) || dims[3] != 1 {
+        return Err(Error::NotAnImage { dims });
+    }
+    Ok((dims[0], dims[1], dims[2] as u8))
+}
+
+/// Serializes a `U8` image as a `((height, width, channels), array)` tuple.
+///
+/// The array must have dims `[height, width, channels, 1]` with 1, 3 or 4
+/// channels, otherwise serialization fails with `Error::DTypeMismatch` or
+/// `Error::NotAnImage` before any data is copied. The array uses the same
+/// representation as `serialize`. `deserialize_image` checks the same
+/// constraints and that the header matches the dims of the array.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate serde;
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Frame {
+///     #[serde(serialize_with = "arrayfire_serde::serialize_image")]
+///     #[serde(deserialize_with = "arrayfire_serde::deserialize_image")]
+///     pixels: arrayfire::Array,
+/// }
+/// # fn main() {}
+/// ```
+pub fn serialize_image<S>(array: &Array, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let header = check_image(array).map_err(ser::Error::custom)?;
+
+    let mut tup = serializer.serialize_tuple(2)?;
+    tup.serialize_element(&header)?;
+    tup.serialize_element(&Ser::new(array))?;
+    tup.end()
+}
+
+/// Deserializes a `U8` image written by `serialize_image`.
+pub fn deserialize_image<'de, D>(deserializer: D) -> Result<Array, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct ImageVisitor;
+
+    impl<'de> Visitor<'de> for ImageVisitor {
+        type Value = Array;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "tuple as a seq of 2 elements")
+        }
+
+        fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+        where
+            V: SeqAccess<'de>,
+        {
+            let header: (u64, u64, u8) = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 2 elements, found fewer"))?;
+            let (height, width, channels) = header;
+            if !CHANNELS.contains(&(channels as u64)) {
+                return Err(de::Error::custom(Error::NotAnImage {
+                    dims: [height, width, channels as u64, 1],
+                }));
+            }
+            let array: De<Array> = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 2 elements, found fewer"))?;
+            if check_image(&array.0).map_err(de::Error::custom)? != header {
+                return Err(de::Error::custom(format_args!(
+                    "image header {:?} does not match array dims {:?}",
+                    header,
+                    array.0.dims().get()
+                )));
+            }
+            Ok(array.0)
+        }
+    }
+
+    deserializer.deserialize_tuple(2, ImageVisitor)
+}
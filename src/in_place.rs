@@ -0,0 +1,255 @@
+//! Deserialization of an `Array` into an existing allocation.
+
+use arrayfire::{Array, DType, Dim4, HasAfEnum};
+use num::Complex;
+use serde::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use alloc::vec::Vec;
+use core::fmt;
+use core::ffi::{c_int, c_void};
+
+use super::packed::{as_bytes, PlainData};
+use super::{
+    array_dtype, check_length, check_size, check_version, empty_array, visit_array_map,
+    ArrayDataSeed, DataEncoding, De, Error, ARRAY_FIELDS,
+};
+
+extern "C" {
+    fn af_write_array(arr: i64, data: *const c_void, bytes: usize, src: c_int) -> c_int;
+}
+
+/// `af_source` value for data in host memory.
+const AF_HOST: c_int = 1;
+
+/// Copies host `data` into the device buffer of `array`.
+fn write_host<T: PlainData>(array: &Array, data: &[T]) -> Result<(), Error> {
+    let bytes = as_bytes(data);
+    let err = unsafe {
+        af_write_array(
+            array.get(),
+            bytes.as_ptr() as *const c_void,
+            bytes.len(),
+            AF_HOST,
+        )
+    };
+    if err != 0 {
+        return Err(Error::BackendError(format!(
+            "af_write_array failed with error code {}",
+            err
+        )));
+    }
+    Ok(())
+}
+
+/// Private seed writing the element data into `array`, whose dtype and dims match the input.
+struct WriteDataSeed<'a> {
+    array: &'a Array,
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for WriteDataSeed<'a> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        fn write<'de, T, D>(deserializer: D, array: &Array) -> Result<(), D::Error>
+        where
+            T: HasAfEnum + PlainData + Deserialize<'de>,
+            D: Deserializer<'de>,
+        {
+            let data: Vec<T> = Vec::deserialize(deserializer)?;
+            check_length::<D::Error>(data.len(), &array.dims())?;
+            write_host(array, &data).map_err(de::Error::custom)
+        }
+
+        fn write_complex<'de, T, D>(deserializer: D, array: &Array) -> Result<(), D::Error>
+        where
+            T: Deserialize<'de>,
+            Complex<T>: HasAfEnum + PlainData,
+            D: Deserializer<'de>,
+        {
+            let data: Vec<Complex<T>> = Vec::<(T, T)>::deserialize(deserializer)?
+                .into_iter()
+                .map(|(re, im)| Complex { re, im })
+                .collect();
+            check_length::<D::Error>(data.len(), &array.dims())?;
+            write_host(array, &data).map_err(de::Error::custom)
+        }
+
+        let array = self.array;
+        let dtype = array_dtype(array).map_err(de::Error::custom)?;
+
+        #[cfg(feature = "zerocopy")]
+        {
+            use super::packed::{data_len, unpack_bits, ByteBuf};
+
+            if !deserializer.is_human_readable() {
+                let bytes = ByteBuf::deserialize(deserializer)?.0;
+                let elements = array.elements();
+                if bytes.len() != data_len(dtype, elements) {
+                    return Err(de::Error::custom(format!(
+                        "expected {} bytes of data, found {}",
+                        data_len(dtype, elements),
+                        bytes.len()
+                    )));
+                }
+                let written = match dtype {
+                    DType::B8 => write_host(array, &unpack_bits(&bytes, elements)),
+                    _ => write_host(array, &bytes),
+                };
+                return written.map_err(de::Error::custom);
+            }
+        }
+
+        match dtype {
+            DType::F32 => write::<f32, D>(deserializer, array),
+            DType::F64 => write::<f64, D>(deserializer, array),
+            DType::S16 => write::<i16, D>(deserializer, array),
+            DType::S32 => write::<i32, D>(deserializer, array),
+            DType::S64 => write::<i64, D>(deserializer, array),
+            DType::U8 => write::<u8, D>(deserializer, array),
+            DType::U16 => write::<u16, D>(deserializer, array),
+            DType::U32 => write::<u32, D>(deserializer, array),
+            DType::U64 => write::<u64, D>(deserializer, array),
+            DType::B8 => write::<bool, D>(deserializer, array),
+            DType::C32 => write_complex::<f32, D>(deserializer, array),
+            DType::C64 => write_complex::<f64, D>(deserializer, array),
+        }
+    }
+}
+
+/// Private seed writing the element data into `place` when it is reusable, and
+/// reading a new array otherwise.
+enum PlaceSeed<'a> {
+    Write(WriteDataSeed<'a>),
+    New(ArrayDataSeed),
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for PlaceSeed<'a> {
+    /// The new array, `None` when the data was written into `place`.
+    type Value = Option<Array>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match self {
+            PlaceSeed::Write(seed) => seed.deserialize(deserializer).map(|()| None),
+            PlaceSeed::New(seed) => seed.deserialize(deserializer).map(Some),
+        }
+    }
+}
+
+/// Whether the data of an array with `dtype` and `dim` can be written into `place`.
+fn reusable(place: &Array, dtype: DType, dim: Dim4) -> bool {
+    dim.elements() > 0
+        && array_dtype(place).ok() == Some(dtype)
+        && place.dims() == dim
+        && place.is_linear()
+        && place.is_owner()
+}
+
+/// Private visitor deserializing an `Array` into `place`.
+struct InPlaceVisitor<'a> {
+    place: &'a mut Array,
+}
+
+impl<'a, 'de> Visitor<'de> for InPlaceVisitor<'a> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "struct ArrayStruct")
+    }
+
+    fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+    where
+        V: SeqAccess<'de>,
+    {
+        let version: u16 = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::custom("expected 4 elements, found fewer"))?;
+        check_version(version)?;
+        let dtype: De<DType> = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::custom("expected 4 elements, found fewer"))?;
+        let dim: De<Dim4> = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::custom("expected 4 elements, found fewer"))?;
+        let (dtype, dim) = (dtype.0, dim.0);
+        check_size(&dim, None).map_err(de::Error::custom)?;
+        if reusable(self.place, dtype, dim) {
+            return seq
+                .next_element_seed(WriteDataSeed { array: self.place })?
+                .ok_or_else(|| de::Error::custom("expected 4 elements, found fewer"));
+        }
+        if dim.elements() == 0 {
+            *self.place = empty_array(dtype, dim).map_err(de::Error::custom)?;
+            return Ok(());
+        }
+        let seed = ArrayDataSeed {
+            dtype,
+            dim,
+            encoding: DataEncoding::Plain,
+        };
+        *self.place = seq
+            .next_element_seed(seed)?
+            .ok_or_else(|| de::Error::custom("expected 4 elements, found fewer"))?;
+        Ok(())
+    }
+
+    fn visit_map<V>(self, map: V) -> Result<Self::Value, V::Error>
+    where
+        V: MapAccess<'de>,
+    {
+        let place: &Array = self.place;
+        let read_dtype = |map: &mut V| map.next_value::<De<DType>>().map(|dtype| dtype.0);
+        let seed = |dtype, dim| {
+            if reusable(place, dtype, dim) {
+                PlaceSeed::Write(WriteDataSeed { array: place })
+            } else {
+                PlaceSeed::New(ArrayDataSeed {
+                    dtype,
+                    dim,
+                    encoding: DataEncoding::Plain,
+                })
+            }
+        };
+        let fields = visit_array_map(map, None, read_dtype, seed)?;
+        match fields.data {
+            Some(Some(array)) => *self.place = array,
+            Some(None) => {}
+            None => {
+                *self.place = empty_array(fields.dtype, fields.dim).map_err(de::Error::custom)?
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Deserializes an `Array` written by `serialize` into `place`.
+///
+/// When the incoming dtype and dims match `place`, the data is copied into its
+/// existing device buffer instead of allocating a new array. Otherwise `place`
+/// is replaced with a freshly allocated array. This avoids allocator churn
+/// when loading many arrays of the same shape in a loop.
+///
+/// ```rust
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+/// extern crate serde;
+///
+/// fn reload<'de, D>(tensor: &mut arrayfire::Array, deserializer: D) -> Result<(), D::Error>
+/// where
+///     D: serde::Deserializer<'de>,
+/// {
+///     arrayfire_serde::deserialize_in_place(deserializer, tensor)
+/// }
+/// # fn main() {}
+/// ```
+pub fn deserialize_in_place<'de, D>(deserializer: D, place: &mut Array) -> Result<(), D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_struct("Array", ARRAY_FIELDS, InPlaceVisitor { place })
+}
@@ -0,0 +1,173 @@
+//! `Array` representation for JavaScript consumers, whose numbers lose
+//! precision above 2^53.
+
+use arrayfire::{Array, Dim4, HasAfEnum};
+use num::{NumCast, PrimInt};
+use serde::de::{self, Visitor};
+use serde::ser;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+use core::str::FromStr;
+
+use super::{
+    check_length, get_data, new_array, serialize_array, ArrayVisitor, DataEncoding, Error,
+    ARRAY_FIELDS,
+};
+
+/// A 64-bit integer serialized as a decimal string.
+pub(crate) struct JsInt<T>(T);
+
+impl<T: PrimInt + fmt::Display> Serialize for JsInt<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0.to_string())
+    }
+}
+
+impl<'de, T: PrimInt + FromStr> Deserialize<'de> for JsInt<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct JsIntVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: PrimInt + FromStr> Visitor<'de> for JsIntVisitor<T> {
+            type Value = JsInt<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "an integer or a string holding one")
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                NumCast::from(value)
+                    .map(JsInt)
+                    .ok_or_else(|| E::invalid_value(de::Unexpected::Signed(value), &self))
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                NumCast::from(value)
+                    .map(JsInt)
+                    .ok_or_else(|| E::invalid_value(de::Unexpected::Unsigned(value), &self))
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                value
+                    .parse()
+                    .map(JsInt)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+            }
+        }
+
+        deserializer.deserialize_any(JsIntVisitor(PhantomData))
+    }
+}
+
+pub(crate) fn serialize_ints<T, S>(array: &Array, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: PrimInt + fmt::Display + HasAfEnum + Default,
+    S: Serializer,
+{
+    get_data::<T>(array)
+        .map_err(ser::Error::custom)?
+        .into_iter()
+        .map(JsInt)
+        .collect::<Vec<_>>()
+        .serialize(serializer)
+}
+
+pub(crate) fn get_array<'de, T, D>(deserializer: D, dim: &Dim4) -> Result<Array, D::Error>
+where
+    T: PrimInt + FromStr + HasAfEnum,
+    D: Deserializer<'de>,
+{
+    let data: Vec<T> = Vec::<JsInt<T>>::deserialize(deserializer)?
+        .into_iter()
+        .map(|i| i.0)
+        .collect();
+    check_length::<D::Error>(data.len(), dim)?;
+    new_array(data.as_slice(), *dim).map_err(de::Error::custom)
+}
+
+fn human_readable_only(function: &str) -> Error {
+    Error::Format(format!(
+        "{} requires a human readable format, as the string transport of 64-bit integers is not recorded",
+        function
+    ))
+}
+
+/// Serializes an `Array` like `serialize`, except that the elements of `S64`
+/// and `U64` arrays are written as decimal strings.
+///
+/// Intended for JSON read by JavaScript, whose numbers cannot hold integers
+/// above 2^53 exactly. Other dtypes, floats included, are written as numbers.
+/// Use `deserialize_js_safe` to read the array back.
+///
+/// Nothing in the output records whether strings were used, so reading it back
+/// relies on the format describing its own values. Formats that are not human
+/// readable fail with an `Error::Format`; use `serialize` for those.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate serde;
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct MyStruct {
+///     #[serde(serialize_with = "arrayfire_serde::serialize_js_safe")]
+///     #[serde(deserialize_with = "arrayfire_serde::deserialize_js_safe")]
+///     ids: arrayfire::Array,
+/// }
+/// # fn main() {}
+/// ```
+pub fn serialize_js_safe<S>(array: &Array, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if !serializer.is_human_readable() {
+        return Err(ser::Error::custom(human_readable_only("serialize_js_safe")));
+    }
+    serialize_array(array, serializer, DataEncoding::JsSafe)
+}
+
+/// Deserializes an `Array` written by `serialize_js_safe`.
+///
+/// `S64` and `U64` elements may be strings or numbers. They are read with
+/// `deserialize_any`, so formats that are not human readable fail with an
+/// `Error::Format`.
+pub fn deserialize_js_safe<'de, D>(deserializer: D) -> Result<Array, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    if !deserializer.is_human_readable() {
+        return Err(de::Error::custom(human_readable_only(
+            "deserialize_js_safe",
+        )));
+    }
+    deserializer.deserialize_struct(
+        "Array",
+        ARRAY_FIELDS,
+        ArrayVisitor {
+            encoding: DataEncoding::JsSafe,
+            max_elements: None,
+            dtype: None,
+            strict: false,
+            probe_empty: false,
+        },
+    )
+}
@@ -0,0 +1,41 @@
+//! JSON reading and writing of `Array` values with `serde_json`.
+//!
+//! Requires the `json` feature.
+//!
+//! ```rust
+//! extern crate arrayfire;
+//! extern crate arrayfire_serde;
+//!
+//! # fn main() {
+//! let array = arrayfire::Array::new(&[1.0f32, 2.0], arrayfire::Dim4::new(&[2, 1, 1, 1]));
+//! let mut json = Vec::new();
+//! arrayfire_serde::json::to_writer(&mut json, &array).unwrap();
+//! let array = arrayfire_serde::json::from_reader(json.as_slice()).unwrap();
+//! # }
+//! ```
+
+use arrayfire::Array;
+use serde_json;
+use std::io::{Read, Write};
+
+use super::{Error, Ser, Serde};
+
+fn json_error(err: serde_json::Error) -> Error {
+    if err.is_io() {
+        Error::Io(err.into())
+    } else {
+        Error::Format(err.to_string())
+    }
+}
+
+/// Writes `array` as JSON in the form of `arrayfire_serde::serialize`.
+pub fn to_writer<W: Write>(w: W, array: &Array) -> Result<(), Error> {
+    serde_json::to_writer(w, &Ser::new(array)).map_err(json_error)
+}
+
+/// Reads an `Array` from JSON written by `to_writer`.
+pub fn from_reader<R: Read>(r: R) -> Result<Array, Error> {
+    serde_json::from_reader::<_, Serde<Array>>(r)
+        .map(Serde::into_inner)
+        .map_err(json_error)
+}
@@ -0,0 +1,203 @@
+//! `Array` representation for JSON, which has no literals for non-finite floats.
+
+use arrayfire::{Array, Dim4, HasAfEnum};
+use num::{Complex, Float, NumCast};
+use serde::de::{self, Visitor};
+use serde::ser;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+
+use super::{
+    check_length, get_data, new_array, serialize_array, ArrayVisitor, DataEncoding, ARRAY_FIELDS,
+};
+
+const NAN: &str = "NaN";
+const INFINITY: &str = "Infinity";
+const NEG_INFINITY: &str = "-Infinity";
+
+/// A float serialized as a number when finite and as a sentinel string otherwise.
+pub(crate) struct JsonFloat<T>(T);
+
+impl<T: Float + Serialize> Serialize for JsonFloat<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let value = self.0;
+        if value.is_nan() {
+            serializer.serialize_str(NAN)
+        } else if value.is_infinite() {
+            serializer.serialize_str(if value > T::zero() {
+                INFINITY
+            } else {
+                NEG_INFINITY
+            })
+        } else {
+            value.serialize(serializer)
+        }
+    }
+}
+
+impl<'de, T: Float> Deserialize<'de> for JsonFloat<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct JsonFloatVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Float> Visitor<'de> for JsonFloatVisitor<T> {
+            type Value = JsonFloat<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    formatter,
+                    "a number or one of \"{}\", \"{}\", \"{}\"",
+                    NAN, INFINITY, NEG_INFINITY
+                )
+            }
+
+            fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                NumCast::from(value)
+                    .map(JsonFloat)
+                    .ok_or_else(|| E::custom(format!("{} is out of range", value)))
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                self.visit_f64(value as f64)
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                self.visit_f64(value as f64)
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                match value {
+                    NAN => Ok(JsonFloat(T::nan())),
+                    INFINITY => Ok(JsonFloat(T::infinity())),
+                    NEG_INFINITY => Ok(JsonFloat(T::neg_infinity())),
+                    _ => Err(E::invalid_value(de::Unexpected::Str(value), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(JsonFloatVisitor(PhantomData))
+    }
+}
+
+pub(crate) fn serialize_floats<T, S>(array: &Array, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Float + HasAfEnum + Default + Serialize,
+    S: Serializer,
+{
+    get_data::<T>(array)
+        .map_err(ser::Error::custom)?
+        .into_iter()
+        .map(JsonFloat)
+        .collect::<Vec<_>>()
+        .serialize(serializer)
+}
+
+pub(crate) fn serialize_complex<T, S>(array: &Array, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Float + Default + Serialize,
+    Complex<T>: HasAfEnum,
+    S: Serializer,
+{
+    get_data::<Complex<T>>(array)
+        .map_err(ser::Error::custom)?
+        .into_iter()
+        .map(|c| (JsonFloat(c.re), JsonFloat(c.im)))
+        .collect::<Vec<_>>()
+        .serialize(serializer)
+}
+
+pub(crate) fn get_array<'de, T, D>(deserializer: D, dim: &Dim4) -> Result<Array, D::Error>
+where
+    T: Float + HasAfEnum,
+    D: Deserializer<'de>,
+{
+    let data: Vec<T> = Vec::<JsonFloat<T>>::deserialize(deserializer)?
+        .into_iter()
+        .map(|f| f.0)
+        .collect();
+    check_length::<D::Error>(data.len(), dim)?;
+    new_array(data.as_slice(), *dim).map_err(de::Error::custom)
+}
+
+pub(crate) fn get_complex_array<'de, T, D>(deserializer: D, dim: &Dim4) -> Result<Array, D::Error>
+where
+    T: Float,
+    Complex<T>: HasAfEnum,
+    D: Deserializer<'de>,
+{
+    let data: Vec<Complex<T>> = Vec::<(JsonFloat<T>, JsonFloat<T>)>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(re, im)| Complex { re: re.0, im: im.0 })
+        .collect();
+    check_length::<D::Error>(data.len(), dim)?;
+    new_array(data.as_slice(), *dim).map_err(de::Error::custom)
+}
+
+/// Serializes an `Array` like `serialize`, except that non-finite values of
+/// float and complex arrays are written as the strings `"NaN"`, `"Infinity"`
+/// and `"-Infinity"`.
+///
+/// Intended for JSON, which cannot represent non-finite numbers.
+/// Use `deserialize_json_safe` to read the array back.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate serde;
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct MyStruct {
+///     #[serde(serialize_with = "arrayfire_serde::serialize_json_safe")]
+///     #[serde(deserialize_with = "arrayfire_serde::deserialize_json_safe")]
+///     weights: arrayfire::Array,
+/// }
+/// # fn main() {}
+/// ```
+pub fn serialize_json_safe<S>(array: &Array, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serialize_array(array, serializer, DataEncoding::JsonSafe)
+}
+
+/// Deserializes an `Array` written by `serialize_json_safe`.
+///
+/// Float values are read with `deserialize_any`, so this requires a
+/// self-describing format.
+pub fn deserialize_json_safe<'de, D>(deserializer: D) -> Result<Array, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_struct(
+        "Array",
+        ARRAY_FIELDS,
+        ArrayVisitor {
+            encoding: DataEncoding::JsonSafe,
+            max_elements: None,
+            dtype: None,
+            strict: false,
+            probe_empty: false,
+        },
+    )
+}
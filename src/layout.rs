@@ -0,0 +1,116 @@
+//! `Array` representation recording its layout role as a sanity check.
+
+use arrayfire::Array;
+use core::fmt;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::{self, SerializeTuple};
+use serde::{Deserializer, Serializer};
+
+use super::{backend_call, De, Error, Ser};
+
+const VECTOR: u8 = 1;
+const ROW: u8 = 2;
+const COLUMN: u8 = 4;
+
+/// The layout predicates of an `Array`, as reported by arrayfire's
+/// `is_vector`, `is_row` and `is_column`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Layout {
+    pub vector: bool,
+    pub row: bool,
+    pub column: bool,
+}
+
+impl Layout {
+    /// Queries the layout predicates of `array`.
+    pub fn of(array: &Array) -> Result<Layout, Error> {
+        backend_call(|| Layout {
+            vector: array.is_vector(),
+            row: array.is_row(),
+            column: array.is_column(),
+        })
+    }
+
+    fn to_bits(self) -> u8 {
+        let mut bits = 0;
+        if self.vector {
+            bits |= VECTOR;
+        }
+        if self.row {
+            bits |= ROW;
+        }
+        if self.column {
+            bits |= COLUMN;
+        }
+        bits
+    }
+
+    fn from_bits(bits: u8) -> Option<Layout> {
+        if bits & !(VECTOR | ROW | COLUMN) != 0 {
+            return None;
+        }
+        Some(Layout {
+            vector: bits & VECTOR != 0,
+            row: bits & ROW != 0,
+            column: bits & COLUMN != 0,
+        })
+    }
+}
+
+/// Serializes an `Array` together with its `Layout`, as a `(layout, array)` tuple.
+///
+/// The layout is a `u8` with bit 0 set for a vector, bit 1 for a row and bit 2
+/// for a column. The array uses the same representation as `serialize`.
+/// `deserialize_with_layout` checks the reconstructed array against the
+/// recorded layout, catching dims that did not survive the round trip.
+pub fn serialize_with_layout<S>(array: &Array, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let layout = Layout::of(array).map_err(ser::Error::custom)?;
+
+    let mut tup = serializer.serialize_tuple(2)?;
+    tup.serialize_element(&layout.to_bits())?;
+    tup.serialize_element(&Ser::new(array))?;
+    tup.end()
+}
+
+/// Deserializes an `Array` written by `serialize_with_layout`.
+///
+/// Fails with `Error::LayoutMismatch` if the array does not have the recorded layout.
+pub fn deserialize_with_layout<'de, D>(deserializer: D) -> Result<Array, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct LayoutArrayVisitor;
+
+    impl<'de> Visitor<'de> for LayoutArrayVisitor {
+        type Value = Array;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "tuple as a seq of 2 elements")
+        }
+
+        fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+        where
+            V: SeqAccess<'de>,
+        {
+            let bits: u8 = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 2 elements, found fewer"))?;
+            let expected = Layout::from_bits(bits).ok_or_else(|| {
+                de::Error::custom(format_args!("invalid layout bits {:#04x}", bits))
+            })?;
+            let array: De<Array> = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 2 elements, found fewer"))?;
+            let found = Layout::of(&array.0).map_err(de::Error::custom)?;
+            if found != expected {
+                return Err(de::Error::custom(Error::LayoutMismatch { expected, found }));
+            }
+            Ok(array.0)
+        }
+    }
+
+    deserializer.deserialize_tuple(2, LayoutArrayVisitor)
+}
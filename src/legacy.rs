@@ -0,0 +1,158 @@
+//! Reading arrays written before `DType` was serialized through a fixed tag table.
+
+use alloc::vec::Vec;
+use arrayfire::{Array, DType, Dim4, HasAfEnum};
+use core::ffi::c_int;
+use core::fmt;
+use num::Complex;
+use serde::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+
+use super::{
+    check_length, check_size, dtype_from_discriminant, dtype_from_tag, dtype_to_tag, empty_array,
+    new_array, visit_array_map, ArrayDataSeed, DataEncoding, De, Error,
+};
+
+/// Maps a dtype written as arrayfire 3.5's `af_dtype` discriminant to its tag
+/// in the current format, see `Ser<DType>`.
+///
+/// The tag table was chosen to match those discriminants, so today every valid
+/// discriminant maps to itself. Other values fail with `Error::InvalidDType`.
+pub fn migrate_dtype_tag(old: u8) -> Result<u8, Error> {
+    dtype_from_discriminant(c_int::from(old)).map(dtype_to_tag)
+}
+
+/// Private helper reading a dtype stored as an `af_dtype` discriminant.
+struct LegacyDType(DType);
+
+impl<'de> Deserialize<'de> for LegacyDType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let old = u8::deserialize(deserializer)?;
+        migrate_dtype_tag(old)
+            .and_then(|tag| dtype_from_tag(u64::from(tag)))
+            .map(LegacyDType)
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Reads the data element of the legacy tuple, which is a sequence of elements
+/// in every format and present even when the array is empty.
+struct LegacyDataSeed {
+    dtype: DType,
+    dim: Dim4,
+}
+
+impl<'de> DeserializeSeed<'de> for LegacyDataSeed {
+    type Value = Array;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        fn get_array<'de, T, D>(data: Vec<T>, dtype: DType, dim: Dim4) -> Result<Array, D::Error>
+        where
+            T: HasAfEnum,
+            D: Deserializer<'de>,
+        {
+            check_length::<D::Error>(data.len(), &dim)?;
+            if data.is_empty() {
+                return empty_array(dtype, dim).map_err(de::Error::custom);
+            }
+            new_array(data.as_slice(), dim).map_err(de::Error::custom)
+        }
+
+        fn get_complex<'de, T, D>(deserializer: D) -> Result<Vec<Complex<T>>, D::Error>
+        where
+            T: Deserialize<'de>,
+            D: Deserializer<'de>,
+        {
+            Ok(Vec::<(T, T)>::deserialize(deserializer)?
+                .into_iter()
+                .map(|(re, im)| Complex { re, im })
+                .collect())
+        }
+
+        let (dtype, dim) = (self.dtype, self.dim);
+        match dtype {
+            DType::F32 => get_array::<f32, D>(Vec::deserialize(deserializer)?, dtype, dim),
+            DType::F64 => get_array::<f64, D>(Vec::deserialize(deserializer)?, dtype, dim),
+            DType::C32 => get_array::<Complex<f32>, D>(get_complex(deserializer)?, dtype, dim),
+            DType::C64 => get_array::<Complex<f64>, D>(get_complex(deserializer)?, dtype, dim),
+            DType::B8 => get_array::<bool, D>(Vec::deserialize(deserializer)?, dtype, dim),
+            DType::S16 => get_array::<i16, D>(Vec::deserialize(deserializer)?, dtype, dim),
+            DType::S32 => get_array::<i32, D>(Vec::deserialize(deserializer)?, dtype, dim),
+            DType::S64 => get_array::<i64, D>(Vec::deserialize(deserializer)?, dtype, dim),
+            DType::U8 => get_array::<u8, D>(Vec::deserialize(deserializer)?, dtype, dim),
+            DType::U16 => get_array::<u16, D>(Vec::deserialize(deserializer)?, dtype, dim),
+            DType::U32 => get_array::<u32, D>(Vec::deserialize(deserializer)?, dtype, dim),
+            DType::U64 => get_array::<u64, D>(Vec::deserialize(deserializer)?, dtype, dim),
+        }
+    }
+}
+
+/// Deserializes an `Array` written with the dtype stored as arrayfire's `af_dtype`
+/// discriminant, converting it with `migrate_dtype_tag`.
+///
+/// Accepts the unversioned `(dtype, dims, data)` tuple of those releases, where
+/// the data is a sequence of elements even for empty arrays, and in self-describing
+/// formats also the struct form of `serialize_human`.
+pub fn deserialize_legacy<'de, D>(deserializer: D) -> Result<Array, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct LegacyArrayVisitor;
+
+    impl<'de> Visitor<'de> for LegacyArrayVisitor {
+        type Value = Array;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "struct ArrayStruct")
+        }
+
+        fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+        where
+            V: SeqAccess<'de>,
+        {
+            let dtype: LegacyDType = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 3 elements, found fewer"))?;
+            let dim: De<Dim4> = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 3 elements, found fewer"))?;
+            check_size(&dim.0, None).map_err(de::Error::custom)?;
+            let seed = LegacyDataSeed {
+                dtype: dtype.0,
+                dim: dim.0,
+            };
+            seq.next_element_seed(seed)?
+                .ok_or_else(|| de::Error::custom("expected 3 elements, found fewer"))
+        }
+
+        fn visit_map<V>(self, map: V) -> Result<Self::Value, V::Error>
+        where
+            V: MapAccess<'de>,
+        {
+            let read_dtype = |map: &mut V| map.next_value::<LegacyDType>().map(|dtype| dtype.0);
+            let seed = |dtype, dim| ArrayDataSeed {
+                dtype,
+                dim,
+                encoding: DataEncoding::Plain,
+            };
+            let fields = visit_array_map(map, None, read_dtype, seed)?;
+            match fields.data {
+                Some(array) => Ok(array),
+                None => empty_array(fields.dtype, fields.dim).map_err(de::Error::custom),
+            }
+        }
+    }
+
+    // the legacy tuple has no version element, so compact formats read 3 elements
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(LegacyArrayVisitor)
+    } else {
+        deserializer.deserialize_tuple(3, LegacyArrayVisitor)
+    }
+}
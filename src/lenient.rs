@@ -0,0 +1,167 @@
+//! Lenient `Array` representation for hand-written, config-style inputs.
+
+use arrayfire::{Array, DType, Dim4, HasAfEnum};
+use num::Complex;
+use serde::de::{self, DeserializeSeed, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::{check_length, check_version, empty_array, new_array, ArrayField, De, ARRAY_FIELDS};
+
+/// Seed deserializing element data, inferring one-dimensional dims when none are given.
+struct LenientDataSeed {
+    dtype: DType,
+    dim: Option<Dim4>,
+}
+
+/// Builds an array from `data`, checking it against `dim` or inferring `[len, 1, 1, 1]`.
+fn build<T: HasAfEnum, E: de::Error>(
+    dtype: DType,
+    data: Vec<T>,
+    dim: Option<Dim4>,
+) -> Result<Array, E> {
+    let dim = dim.unwrap_or_else(|| Dim4::new(&[data.len() as u64, 1, 1, 1]));
+    check_length::<E>(data.len(), &dim)?;
+    if data.is_empty() {
+        return empty_array(dtype, dim).map_err(E::custom);
+    }
+    new_array(data.as_slice(), dim).map_err(E::custom)
+}
+
+impl<'de> DeserializeSeed<'de> for LenientDataSeed {
+    type Value = Array;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        fn get_array<'de, T, D>(
+            deserializer: D,
+            dtype: DType,
+            dim: Option<Dim4>,
+        ) -> Result<Array, D::Error>
+        where
+            T: HasAfEnum + Deserialize<'de>,
+            D: Deserializer<'de>,
+        {
+            build(dtype, Vec::<T>::deserialize(deserializer)?, dim)
+        }
+
+        fn get_complex_array<'de, T, D>(
+            deserializer: D,
+            dtype: DType,
+            dim: Option<Dim4>,
+        ) -> Result<Array, D::Error>
+        where
+            T: Deserialize<'de>,
+            Complex<T>: HasAfEnum,
+            D: Deserializer<'de>,
+        {
+            let data: Vec<Complex<T>> = Vec::<(T, T)>::deserialize(deserializer)?
+                .into_iter()
+                .map(|(re, im)| Complex { re, im })
+                .collect();
+            build(dtype, data, dim)
+        }
+
+        let (dtype, dim) = (self.dtype, self.dim);
+        match dtype {
+            DType::F32 => get_array::<f32, D>(deserializer, dtype, dim),
+            DType::F64 => get_array::<f64, D>(deserializer, dtype, dim),
+            DType::S16 => get_array::<i16, D>(deserializer, dtype, dim),
+            DType::S32 => get_array::<i32, D>(deserializer, dtype, dim),
+            DType::S64 => get_array::<i64, D>(deserializer, dtype, dim),
+            DType::U8 => get_array::<u8, D>(deserializer, dtype, dim),
+            DType::U16 => get_array::<u16, D>(deserializer, dtype, dim),
+            DType::U32 => get_array::<u32, D>(deserializer, dtype, dim),
+            DType::U64 => get_array::<u64, D>(deserializer, dtype, dim),
+            DType::B8 => get_array::<bool, D>(deserializer, dtype, dim),
+            DType::C32 => get_complex_array::<f32, D>(deserializer, dtype, dim),
+            DType::C64 => get_complex_array::<f64, D>(deserializer, dtype, dim),
+        }
+    }
+}
+
+/// Deserializes an `Array` from a map in which only `data` is required.
+///
+/// `dtype` defaults to `F32` and `dims` to `[len, 1, 1, 1]`, where `len` is the
+/// number of elements in `data`. `version` may be omitted as well. The fields
+/// may be written in any order, except that `dtype` and `dims` must precede
+/// `data` when given, since the data is read with them. A map written by
+/// `serialize_human` is accepted unchanged.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate serde;
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// #[derive(Deserialize)]
+/// struct Fixture {
+///     // e.g. `weights: { data: [0.5, 0.25, 0.25] }` in YAML
+///     #[serde(deserialize_with = "arrayfire_serde::deserialize_lenient")]
+///     weights: arrayfire::Array,
+/// }
+/// # fn main() {}
+/// ```
+pub fn deserialize_lenient<'de, D>(deserializer: D) -> Result<Array, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct LenientVisitor;
+
+    impl<'de> Visitor<'de> for LenientVisitor {
+        type Value = Array;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "a map with a `data` field")
+        }
+
+        fn visit_map<V>(self, mut map: V) -> Result<Self::Value, V::Error>
+        where
+            V: MapAccess<'de>,
+        {
+            let mut version: Option<u16> = None;
+            let mut dtype: Option<DType> = None;
+            let mut dim: Option<Dim4> = None;
+            let mut array: Option<Array> = None;
+            while let Some(key) = map.next_key::<ArrayField>()? {
+                let seen = match key {
+                    ArrayField::Version => version.is_some(),
+                    ArrayField::Dtype => dtype.is_some(),
+                    ArrayField::Dims => dim.is_some(),
+                    ArrayField::Data => array.is_some(),
+                };
+                if seen {
+                    return Err(de::Error::duplicate_field(key.name()));
+                }
+                if key != ArrayField::Data && array.is_some() {
+                    return Err(de::Error::custom(format!(
+                        "field `{}` must precede `data`",
+                        key.name()
+                    )));
+                }
+                match key {
+                    ArrayField::Version => {
+                        let value: u16 = map.next_value()?;
+                        check_version(value)?;
+                        version = Some(value);
+                    }
+                    ArrayField::Dtype => dtype = Some(map.next_value::<De<DType>>()?.0),
+                    ArrayField::Dims => dim = Some(map.next_value::<De<Dim4>>()?.0),
+                    ArrayField::Data => {
+                        array = Some(map.next_value_seed(LenientDataSeed {
+                            dtype: dtype.unwrap_or(DType::F32),
+                            dim,
+                        })?)
+                    }
+                }
+            }
+            array.ok_or_else(|| de::Error::missing_field(ArrayField::Data.name()))
+        }
+    }
+
+    deserializer.deserialize_struct("Array", ARRAY_FIELDS, LenientVisitor)
+}
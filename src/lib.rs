@@ -4,9 +4,20 @@
 //!
 //! As of now the supported types are:
 //!
-//! * `arrayfire::Array` (non-complex internal type)
+//! * `arrayfire::Array`
 //! * `arrayfire::Dim4`
 //! * `arrayfire::DType`
+//! * `arrayfire::Seq`
+//! * `arrayfire::Backend`
+//! * `arrayfire::RandomEngineType`
+//! * `arrayfire::RandomEngine`
+//!
+//! Half precision (`f16`) and signed 8-bit (`i8`) arrays are not supported: the
+//! `DType` enum of the `arrayfire` 3.5 bindings this crate builds against has
+//! no `F16` or `S8` variant, so the dtype of such an array, as created by a
+//! newer arrayfire library, cannot be reported or written as a tag. These
+//! arrays fail to serialize with `Error::InvalidDType` holding arrayfire's raw
+//! dtype value, e.g. 12 for `f16`, instead of panicking.
 //!
 //! # Examples
 //!
@@ -29,14 +40,223 @@
 //! }
 //! # fn main() {}
 //! ```
+//!
+//! `Vec` fields of `arrayfire` types use the `seq` module instead,
+//! e.g. `#[serde(with = "arrayfire_serde::seq")]` on a `Vec<arrayfire::Array>`,
+//! `Option` fields the `option` module, `Box` fields the `boxed` module,
+//! `(Array, Dim4, DType)` fields the
+//! `triple` module, and `HashMap<String, Array>` or `BTreeMap<String, Array>`
+//! fields the `named_arrays` module. With the `serde_with` feature,
+//! `ArrayAsTuple` serves the same purpose in `#[serde_as]` attributes and
+//! composes with `serde_with`'s own adapters, e.g.
+//! `#[serde_as(as = "Option<Vec<ArrayAsTuple>>")]`.
+//!
+//! Arrays can also be written to `std::io` streams in a fixed binary layout,
+//! independent of any serde format, with `write_array` and `read_array`.
+//! `ArrayWriter` and `ArrayReader` store a sequence of arrays in one stream.
+//! `from_npy` and `to_npy` read and write NumPy's `.npy` files.
+//!
+//! # Format
+//!
+//! The representation written by `serialize` is frozen; stored data stays
+//! readable by every later release with the same format version. An `Array`
+//! is a `(version, dtype, dims, data)` tuple:
+//!
+//! * `version`: `u16`, currently 1.
+//! * `dtype`: `u8` tag, numbered as documented on `Ser<DType>`. Human-readable
+//!   formats get the lowercase name instead, e.g. `"f64"`, and still accept
+//!   the tag.
+//! * `dims`: four `u64`, in arrayfire's order with the first dimension first.
+//! * `data`: a sequence of the elements in column-major order, i.e. with the
+//!   first dimension varying fastest. Complex elements are `(re, im)` pairs and
+//!   `B8` elements are `bool`. Omitted for arrays without elements. The
+//!   `zerocopy` feature changes this element in formats that are not human
+//!   readable, see below.
+//!
+//! The binary format of `write_array` is specified at the top of
+//! `src/binary.rs`. Both are checked against hand-written fixtures in
+//! `tests/golden`, which must never be regenerated.
+//!
+//! # Features
+//!
+//! * `arrow`: `to_arrow_buffer`, copying array data into an Apache Arrow buffer.
+//! * `base64`: `serialize_base64` and `deserialize_base64`, embedding array data
+//!   as a single base64 string, e.g. in JSON.
+//! * `bincode`: `to_bytes` and `from_bytes` helpers.
+//! * `json`: the `json` module with `to_writer` and `from_reader` helpers.
+//! * `rmp-serde`: the `msgpack` module with `to_vec` and `from_slice` helpers
+//!   for MessagePack. Enables `std`.
+//! * `logging`: logs the dtype, element count and size of arrays above a
+//!   threshold as they are serialized or deserialized, with the `log` crate.
+//!   See `set_log_threshold`. The output is unaffected.
+//! * `zerocopy`: in formats that are not human readable, array data is written
+//!   as a single byte string in host byte order instead of element by element.
+//!   Data written this way can only be read with the feature enabled. The
+//!   element slices are converted with `bytemuck`.
+//! * `serde_with`: `ArrayAsTuple`, for use with `serde_with::serde_as`.
+//! * `zstd`: `serialize_compressed` and `deserialize_compressed`, the binary
+//!   format of `write_array` in a zstd stream. Enables `std`.
+//! * `test-util`: `assert_roundtrips` for the tests of downstream crates.
+//!   Enables `bincode`.
+//! * `no-panic`: denies `panic!`, `unwrap`, `expect` and similar in this crate
+//!   under clippy, so every failure has to surface as an `Err`. Panics raised by
+//!   arrayfire's default error handler are not covered.
+//! * `std` (default): the binary format, `ArrayReader`/`ArrayWriter`, `.npy`
+//!   files, `Error::Io` and catching arrayfire's panics as
+//!   `Error::BackendError`. Without it the crate is `no_std` and only needs
+//!   `alloc`; the serde impls and the other representations are unaffected.
+//!   `json` enables `std`.
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(
+    feature = "no-panic",
+    deny(
+        clippy::panic,
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::unreachable,
+        clippy::todo,
+        clippy::unimplemented
+    )
+)]
+#[macro_use]
+extern crate alloc;
 extern crate arrayfire;
+#[cfg(feature = "arrow")]
+extern crate arrow_buffer;
+#[cfg(feature = "arrow")]
+extern crate arrow_schema;
+#[cfg(feature = "bincode")]
+extern crate bincode;
+#[cfg(feature = "zerocopy")]
+extern crate bytemuck;
+#[cfg(feature = "std")]
+extern crate core;
+extern crate crc32fast;
+#[cfg(feature = "logging")]
+extern crate log;
+extern crate num;
+#[cfg(feature = "rmp-serde")]
+extern crate rmp_serde;
 extern crate serde;
+#[cfg(feature = "json")]
+extern crate serde_json;
+#[cfg(feature = "serde_with")]
+extern crate serde_with;
+#[cfg(feature = "zstd")]
+extern crate zstd;
 
-use arrayfire::{Array, DType, Dim4, HasAfEnum};
+use arrayfire::{Array, Backend, DType, Dim4, HasAfEnum, RandomEngine, RandomEngineType, Seq};
+use num::Complex;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use serde::de::{SeqAccess, Visitor};
-use serde::ser::SerializeTuple;
-use std::fmt;
+use serde::de::value::SeqDeserializer;
+use serde::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde::ser::{self, SerializeStruct, SerializeTuple};
+#[cfg(any(feature = "std", feature = "bincode"))]
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::ffi::{c_int, c_uint};
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+#[cfg(feature = "std")]
+use std::panic::{self, AssertUnwindSafe};
+
+#[cfg(feature = "arrow")]
+mod arrow;
+#[cfg(feature = "serde_with")]
+mod as_tuple;
+#[cfg(feature = "base64")]
+mod base64;
+#[cfg(feature = "std")]
+mod binary;
+pub mod boxed;
+mod buffer;
+mod chunked;
+mod compare;
+mod complex_layout;
+#[cfg(feature = "zstd")]
+mod compressed;
+pub mod dim4_flexible;
+pub mod dim4_named;
+pub mod dim4_vec;
+pub mod dtype_vec;
+mod error;
+mod finite;
+mod image;
+mod in_place;
+mod js_safe;
+#[cfg(feature = "json")]
+pub mod json;
+mod json_safe;
+mod layout;
+mod legacy;
+mod lenient;
+#[cfg(feature = "logging")]
+mod logging;
+mod meta;
+#[cfg(feature = "rmp-serde")]
+pub mod msgpack;
+pub mod named_arrays;
+#[cfg(feature = "std")]
+mod npy;
+pub mod option;
+mod order;
+mod packed;
+mod rounded;
+pub mod seq;
+mod sparse;
+#[cfg(feature = "std")]
+mod stream;
+#[cfg(feature = "test-util")]
+mod test_util;
+pub mod triple;
+mod typed;
+mod view_dims;
+#[cfg(feature = "zerocopy")]
+mod zerocopy;
+
+#[cfg(feature = "arrow")]
+pub use arrow::to_arrow_buffer;
+#[cfg(feature = "serde_with")]
+pub use as_tuple::ArrayAsTuple;
+#[cfg(feature = "base64")]
+pub use base64::{deserialize_base64, serialize_base64};
+#[cfg(feature = "std")]
+pub use binary::{
+    deserialize_window, from_slice, read_array, read_array_header, write_array,
+    write_array_with_checksum,
+};
+pub use chunked::ChunkedArray;
+pub use compare::{arrays_approx_equal, arrays_equal};
+pub use complex_layout::{deserialize_complex, serialize_complex, ComplexLayout};
+#[cfg(feature = "zstd")]
+pub use compressed::{deserialize_compressed, serialize_compressed};
+pub use error::Error;
+pub use finite::serialize_checked_finite;
+pub use image::{deserialize_image, serialize_image};
+pub use in_place::deserialize_in_place;
+pub use js_safe::{deserialize_js_safe, serialize_js_safe};
+pub use json_safe::{deserialize_json_safe, serialize_json_safe};
+pub use layout::{deserialize_with_layout, serialize_with_layout, Layout};
+pub use legacy::{deserialize_legacy, migrate_dtype_tag};
+pub use lenient::deserialize_lenient;
+#[cfg(feature = "logging")]
+pub use logging::set_log_threshold;
+pub use meta::{deserialize_meta, serialize_meta, ArrayMeta};
+#[cfg(feature = "std")]
+pub use npy::{from_npy, to_npy};
+pub use order::{deserialize_ordered, serialize_ordered, Order};
+pub use packed::{deserialize_packed, serialize_packed};
+pub use rounded::serialize_rounded;
+pub use seq::{deserialize as deserialize_seq, serialize as serialize_seq};
+pub use sparse::CooSparse;
+#[cfg(feature = "test-util")]
+pub use test_util::assert_roundtrips;
+#[cfg(feature = "std")]
+pub use stream::{ArrayReader, ArrayWriter};
+pub use typed::TypedArray;
+pub use view_dims::ViewDims;
 
 /// Exposed serialization function used by the `serde` attributes:
 ///
@@ -57,6 +277,31 @@ use std::fmt;
 /// }
 /// # fn main() {}
 /// ```
+///
+/// `value` is only borrowed for the duration of the call, so an owned `Array`
+/// is serialized with `arrayfire_serde::serialize(&array, serializer)`. Fields
+/// holding `&Array` or `&mut Array` work the same way.
+///
+/// The bound `for<'a> Ser<'a, T>: Serialize` reads "`Ser` can serialize a
+/// borrow of `T` of any lifetime", which holds for every type supported by this
+/// crate. Generic code forwarding to `serialize` repeats the bound:
+///
+/// ```rust
+/// extern crate serde;
+/// extern crate arrayfire_serde;
+///
+/// use arrayfire_serde::Ser;
+/// use serde::{Serialize, Serializer};
+///
+/// fn save<T, S>(value: T, serializer: S) -> Result<S::Ok, S::Error>
+/// where
+///     for<'a> Ser<'a, T>: Serialize,
+///     S: Serializer,
+/// {
+///     arrayfire_serde::serialize(&value, serializer)
+/// }
+/// # fn main() {}
+/// ```
 pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
 where
     for<'a> Ser<'a, T>: Serialize,
@@ -99,11 +344,23 @@ impl<'a, T> Ser<'a, T>
 where
     Ser<'a, T>: serde::Serialize,
 {
+    /// Wraps `value`, same as `Ser::from(value)`.
     pub fn new(value: &'a T) -> Self {
         Ser(value)
     }
 }
 
+/// Allows `value.into()` wherever a `Ser` is expected, e.g. in generic code
+/// bounded on `Into<Ser<'a, T>>`.
+impl<'a, T> From<&'a T> for Ser<'a, T>
+where
+    Ser<'a, T>: serde::Serialize,
+{
+    fn from(value: &'a T) -> Self {
+        Ser(value)
+    }
+}
+
 /// Wrapper tuple struct to provide `serde::Deserialize` trait for arrayfire types.
 pub struct De<T>(T);
 
@@ -116,7 +373,33 @@ where
     }
 }
 
-struct Serde<T>(pub T);
+/// Transparent newtype implementing both `serde::Serialize` and `serde::Deserialize`
+/// for arrayfire types.
+///
+/// Unlike `Ser` and `De` it owns the value and dereferences to it, so it can be used
+/// directly as a field type where `#[serde(with)]` is not available,
+/// e.g. in a `HashMap<String, Serde<arrayfire::Array>>`.
+pub struct Serde<T>(pub T);
+
+impl<T> Serde<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Serde<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Serde<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
 
 impl<T> Serialize for Serde<T>
 where
@@ -176,10 +459,21 @@ impl<'de> Deserialize<'de> for De<Dim4> {
             where
                 V: SeqAccess<'de>,
             {
-                let d0: u64 = visitor.next_element()?.expect("has element");
-                let d1: u64 = visitor.next_element()?.expect("has element");
-                let d2: u64 = visitor.next_element()?.expect("has element");
-                let d3: u64 = visitor.next_element()?.expect("has element");
+                let d0: u64 = visitor
+                    .next_element()?
+                    .ok_or_else(|| de::Error::custom("expected 4 elements, found fewer"))?;
+                let d1: u64 = visitor
+                    .next_element()?
+                    .ok_or_else(|| de::Error::custom("expected 4 elements, found fewer"))?;
+                let d2: u64 = visitor
+                    .next_element()?
+                    .ok_or_else(|| de::Error::custom("expected 4 elements, found fewer"))?;
+                let d3: u64 = visitor
+                    .next_element()?
+                    .ok_or_else(|| de::Error::custom("expected 4 elements, found fewer"))?;
+                if visitor.next_element::<de::IgnoredAny>()?.is_some() {
+                    return Err(de::Error::custom("expected 4 elements, found more"));
+                }
                 let dim = Dim4::new(&[d0, d1, d2, d3]);
                 Ok(De(dim))
             }
@@ -189,13 +483,31 @@ impl<'de> Deserialize<'de> for De<Dim4> {
     }
 }
 
+/// `DType` is serialized as a `u8` tag with a fixed numbering:
+///
+/// | tag | dtype | tag | dtype | tag | dtype |
+/// |-----|-------|-----|-------|-----|-------|
+/// | 0   | `F32` | 4   | `B8`  | 8   | `S64` |
+/// | 1   | `C32` | 5   | `S32` | 9   | `U64` |
+/// | 2   | `F64` | 6   | `U32` | 10  | `S16` |
+/// | 3   | `C64` | 7   | `U8`  | 11  | `U16` |
+///
+/// The tags match arrayfire 3.5's `af_dtype` values but do not follow them,
+/// so a reordered `DType` enum in another arrayfire version keeps saved data readable.
+///
+/// Human-readable formats get the lowercase variant name instead, e.g. `"f64"`
+/// for `DType::F64`. Deserializing from them accepts both the name and the tag,
+/// so data written before names were introduced stays readable.
 impl<'a> Serialize for Ser<'a, DType> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let enum_value = *self.0 as u8;
-        serializer.serialize_u8(enum_value)
+        if serializer.is_human_readable() {
+            serializer.serialize_str(dtype_name(*self.0))
+        } else {
+            serializer.serialize_u8(dtype_to_tag(*self.0))
+        }
     }
 }
 
@@ -217,102 +529,1451 @@ impl<'de> Deserialize<'de> for De<DType> {
             where
                 E: serde::de::Error,
             {
-                let dtype: DType = unsafe { std::mem::transmute(i32::from(value)) };
-                Ok(De(dtype))
+                self.visit_u64(u64::from(value))
             }
 
+            // `visit_u16` and `visit_u32` forward here by default
             fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
             where
                 E: serde::de::Error,
             {
-                let dtype: DType = unsafe { std::mem::transmute(value as i32) };
-                Ok(De(dtype))
+                dtype_from_tag(value).map(De).map_err(E::custom)
+            }
+
+            // some formats hand out the tag as a signed integer,
+            // `visit_i8` to `visit_i32` forward here by default
+            fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                if value < 0 {
+                    return Err(E::invalid_value(de::Unexpected::Signed(value), &self));
+                }
+                self.visit_u64(value as u64)
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                dtype_from_name(value)
+                    .map(De)
+                    .ok_or_else(|| E::unknown_variant(value, &DTYPE_NAMES))
             }
         }
 
-        deserializer.deserialize_u8(DTypeVisitor)
+        // human-readable data holds a name, or a tag if written by older releases
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(DTypeVisitor)
+        } else {
+            deserializer.deserialize_u8(DTypeVisitor)
+        }
     }
 }
 
-impl<'a> Serialize for Ser<'a, Array> {
+/// Names of the dtypes in human-readable formats, indexed by tag.
+const DTYPE_NAMES: [&str; 12] = [
+    "f32", "c32", "f64", "c64", "b8", "s32", "u32", "u8", "s64", "u64", "s16", "u16",
+];
+
+/// Returns the human-readable name of `dtype`, see `Ser<DType>`.
+fn dtype_name(dtype: DType) -> &'static str {
+    DTYPE_NAMES[usize::from(dtype_to_tag(dtype))]
+}
+
+/// Maps a human-readable dtype name back to the `DType`.
+fn dtype_from_name(name: &str) -> Option<DType> {
+    let tag = DTYPE_NAMES.iter().position(|&n| n == name)?;
+    dtype_from_tag(tag as u64).ok()
+}
+
+/// Returns the serialized tag of `dtype`, see `Ser<DType>`.
+fn dtype_to_tag(dtype: DType) -> u8 {
+    match dtype {
+        DType::F32 => 0,
+        DType::C32 => 1,
+        DType::F64 => 2,
+        DType::C64 => 3,
+        DType::B8 => 4,
+        DType::S32 => 5,
+        DType::U32 => 6,
+        DType::U8 => 7,
+        DType::S64 => 8,
+        DType::U64 => 9,
+        DType::S16 => 10,
+        DType::U16 => 11,
+    }
+}
+
+/// Maps a serialized `DType` tag back to the `DType`.
+fn dtype_from_tag(value: u64) -> Result<DType, Error> {
+    Ok(match value {
+        0 => DType::F32,
+        1 => DType::C32,
+        2 => DType::F64,
+        3 => DType::C64,
+        4 => DType::B8,
+        5 => DType::S32,
+        6 => DType::U32,
+        7 => DType::U8,
+        8 => DType::S64,
+        9 => DType::U64,
+        10 => DType::S16,
+        11 => DType::U16,
+        _ => return Err(Error::InvalidDType(value)),
+    })
+}
+
+/// `Seq` is serialized as a `(begin, end, step)` tuple.
+///
+/// The span sentinel (`Seq::default()`) is stored as its raw values and
+/// therefore deserializes back to a span.
+impl<'a, T> Serialize for Ser<'a, Seq<T>>
+where
+    T: Copy + Serialize,
+{
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let array: &Array = self.0;
-        let dim = array.dims();
-        let dtype: DType = array.get_type();
-
         let mut tup = serializer.serialize_tuple(3)?;
-        tup.serialize_element(&Ser::new(&dtype))?;
-        tup.serialize_element(&Ser::new(&dim))?;
+        tup.serialize_element(&self.0.begin())?;
+        tup.serialize_element(&self.0.end())?;
+        tup.serialize_element(&self.0.step())?;
+        tup.end()
+    }
+}
+
+impl<'de, T> Deserialize<'de> for De<Seq<T>>
+where
+    T: Copy + Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct SeqVisitor<T>(PhantomData<T>);
+
+        impl<'de, T> Visitor<'de> for SeqVisitor<T>
+        where
+            T: Copy + Deserialize<'de>,
+        {
+            type Value = De<Seq<T>>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "tuple as a seq of 3 elements")
+            }
 
-        fn get_data<T: HasAfEnum>(array: &Array) -> Vec<T> {
-            let mut data: Vec<T> = Vec::with_capacity(array.elements());
-            unsafe {
-                data.set_len(array.elements());
+            fn visit_seq<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
+            where
+                V: SeqAccess<'de>,
+            {
+                let begin: T = visitor
+                    .next_element()?
+                    .ok_or_else(|| de::Error::custom("expected 3 elements, found fewer"))?;
+                let end: T = visitor
+                    .next_element()?
+                    .ok_or_else(|| de::Error::custom("expected 3 elements, found fewer"))?;
+                let step: T = visitor
+                    .next_element()?
+                    .ok_or_else(|| de::Error::custom("expected 3 elements, found fewer"))?;
+                Ok(De(Seq::new(begin, end, step)))
             }
-            array.host(&mut data.as_mut_slice());
-            data
         }
 
-        match dtype {
-            DType::F32 => tup.serialize_element(&get_data::<f32>(array))?,
-            DType::F64 => tup.serialize_element(&get_data::<f64>(array))?,
-            DType::S16 => tup.serialize_element(&get_data::<i16>(array))?,
-            DType::S32 => tup.serialize_element(&get_data::<i32>(array))?,
-            DType::S64 => tup.serialize_element(&get_data::<i64>(array))?,
-            DType::U16 => tup.serialize_element(&get_data::<u16>(array))?,
-            DType::U32 => tup.serialize_element(&get_data::<u32>(array))?,
-            DType::U64 => tup.serialize_element(&get_data::<u64>(array))?,
-            DType::B8 => tup.serialize_element(&get_data::<bool>(array))?,
-            _ => panic!("unimplemented serialization for complex types!"),
+        deserializer.deserialize_tuple(3, SeqVisitor(PhantomData))
+    }
+}
+
+impl<'a> Serialize for Ser<'a, Backend> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let enum_value = *self.0 as u8;
+        serializer.serialize_u8(enum_value)
+    }
+}
+
+impl<'de> Deserialize<'de> for De<Backend> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct BackendVisitor;
+
+        impl<'de> Visitor<'de> for BackendVisitor {
+            type Value = De<Backend>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "u8")
+            }
+
+            fn visit_u8<E>(self, value: u8) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                self.visit_u64(u64::from(value))
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                let backend = match value {
+                    0 => Backend::DEFAULT,
+                    1 => Backend::CPU,
+                    2 => Backend::CUDA,
+                    4 => Backend::OPENCL,
+                    _ => return Err(E::custom(format!("invalid backend discriminant {}", value))),
+                };
+                Ok(De(backend))
+            }
         }
 
+        deserializer.deserialize_u8(BackendVisitor)
+    }
+}
+
+impl<'a> Serialize for Ser<'a, RandomEngineType> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let enum_value = *self.0 as u16;
+        serializer.serialize_u16(enum_value)
+    }
+}
+
+impl<'de> Deserialize<'de> for De<RandomEngineType> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct RandomEngineTypeVisitor;
+
+        impl<'de> Visitor<'de> for RandomEngineTypeVisitor {
+            type Value = De<RandomEngineType>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "u16")
+            }
+
+            fn visit_u16<E>(self, value: u16) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                self.visit_u64(u64::from(value))
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                let engine_type = match value {
+                    100 => RandomEngineType::PHILOX_4X32_10,
+                    200 => RandomEngineType::THREEFRY_2X32_16,
+                    300 => RandomEngineType::MERSENNE_GP11213,
+                    _ => {
+                        return Err(E::custom(format!(
+                            "invalid random engine type discriminant {}",
+                            value
+                        )))
+                    }
+                };
+                Ok(De(engine_type))
+            }
+        }
+
+        deserializer.deserialize_u16(RandomEngineTypeVisitor)
+    }
+}
+
+/// A `RandomEngine` is serialized as its `(engine type, seed)` tuple.
+///
+/// arrayfire does not expose the counter of an engine, so only the seeded
+/// state is captured: an engine deserialized from a freshly seeded one
+/// produces the same sequence, while numbers already drawn are not skipped.
+impl<'a> Serialize for Ser<'a, RandomEngine> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let engine: &RandomEngine = self.0;
+        let (engine_type, seed) =
+            backend_call(|| (engine.get_type(), engine.get_seed())).map_err(ser::Error::custom)?;
+
+        let mut tup = serializer.serialize_tuple(2)?;
+        tup.serialize_element(&Ser::new(&engine_type))?;
+        tup.serialize_element(&seed)?;
         tup.end()
     }
 }
 
-impl<'de> Deserialize<'de> for De<Array> {
+impl<'de> Deserialize<'de> for De<RandomEngine> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        struct ArrayVisitor;
+        struct RandomEngineVisitor;
 
-        impl<'de> Visitor<'de> for ArrayVisitor {
-            type Value = De<Array>;
+        impl<'de> Visitor<'de> for RandomEngineVisitor {
+            type Value = De<RandomEngine>;
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                write!(formatter, "struct ArrayStruct")
+                write!(formatter, "tuple as a seq of 2 elements")
             }
 
-            fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+            fn visit_seq<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
             where
                 V: SeqAccess<'de>,
             {
-                let dtype: De<DType> = seq.next_element()?.expect("has element");
-                let dim: De<Dim4> = seq.next_element()?.expect("has element");
+                let engine_type: De<RandomEngineType> = visitor
+                    .next_element()?
+                    .ok_or_else(|| de::Error::custom("expected 2 elements, found fewer"))?;
+                let seed: u64 = visitor
+                    .next_element()?
+                    .ok_or_else(|| de::Error::custom("expected 2 elements, found fewer"))?;
+                backend_call(|| RandomEngine::new(engine_type.0, Some(seed)))
+                    .map(De)
+                    .map_err(de::Error::custom)
+            }
+        }
 
-                fn get_array<T: HasAfEnum>(data: Option<Vec<T>>, dim: &Dim4) -> Array {
-                    let data: Vec<T> = data.expect("has vector of elements");
-                    Array::new::<T>(data.as_slice(), *dim)
-                }
+        deserializer.deserialize_tuple(2, RandomEngineVisitor)
+    }
+}
+
+/// Version of the `Array` representation, written as its first element.
+const FORMAT_VERSION: u16 = 1;
+
+/// Copies the element data of an array to the host.
+///
+/// Errors raised by arrayfire, e.g. when the device runs out of memory,
+/// are returned as `Error::BackendError` instead of yielding partial data.
+fn get_data<T: HasAfEnum + Clone + Default>(array: &Array) -> Result<Vec<T>, Error> {
+    backend_call(|| {
+        let mut data: Vec<T> = vec![T::default(); array.elements()];
+        if array.is_linear() && array.is_owner() {
+            array.host(data.as_mut_slice());
+        } else {
+            // views created by indexing may be offset or strided into their parent,
+            // so copy out the logical elements into a contiguous array first
+            array.copy().host(data.as_mut_slice());
+        }
+        data
+    })
+}
+
+fn check_version<E: de::Error>(version: u16) -> Result<(), E> {
+    if version != FORMAT_VERSION {
+        return Err(E::custom(format!(
+            "unsupported array format version {} (expected {})",
+            version, FORMAT_VERSION
+        )));
+    }
+    Ok(())
+}
+
+/// Runs `f`, converting a panic raised by arrayfire's default error handler
+/// into `Error::BackendError`.
+#[cfg(feature = "std")]
+fn backend_call<F, R>(f: F) -> Result<R, Error>
+where
+    F: FnOnce() -> R,
+{
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        let msg = match payload.downcast::<String>() {
+            Ok(msg) => *msg,
+            Err(payload) => match payload.downcast::<&str>() {
+                Ok(msg) => msg.to_string(),
+                Err(_) => "unknown error".to_string(),
+            },
+        };
+        Error::BackendError(msg)
+    })
+}
+
+/// Without `std` panics cannot be caught, so they propagate from `f` unchanged.
+#[cfg(not(feature = "std"))]
+fn backend_call<F, R>(f: F) -> Result<R, Error>
+where
+    F: FnOnce() -> R,
+{
+    Ok(f())
+}
+
+/// How the element data of an `Array` is written and read.
+#[derive(Clone, Copy, PartialEq)]
+enum DataEncoding {
+    /// Every element as its native serde type.
+    Plain,
+    /// Non-finite floats as strings, see `serialize_json_safe`.
+    JsonSafe,
+    /// 64-bit integers as decimal strings, see `serialize_js_safe`.
+    JsSafe,
+    /// Every element as its native serde type, copied to the host in chunks of
+    /// at most this many elements, see `ChunkedArray`.
+    Chunked(usize),
+    /// Floats rounded to this many decimals in human-readable formats, see
+    /// `serialize_rounded`.
+    Rounded(u32),
+}
+
+/// Private helper serializing the element data of an `Array` as a sequence,
+/// or as a byte string in compact formats with the `zerocopy` feature.
+struct ArrayData<'a> {
+    array: &'a Array,
+    encoding: DataEncoding,
+}
+
+impl<'a> Serialize for ArrayData<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let array: &Array = self.array;
+        let dtype = array_dtype(array).map_err(ser::Error::custom)?;
+
+        fn serialize_data<T, S>(array: &Array, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            T: HasAfEnum + Clone + Default + Serialize,
+            S: Serializer,
+        {
+            get_data::<T>(array)
+                .map_err(ser::Error::custom)?
+                .serialize(serializer)
+        }
+
+        fn serialize_complex_data<T, S>(array: &Array, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            T: Copy + Default + Serialize,
+            Complex<T>: HasAfEnum,
+            S: Serializer,
+        {
+            get_data::<Complex<T>>(array)
+                .map_err(ser::Error::custom)?
+                .iter()
+                .map(|c| (c.re, c.im))
+                .collect::<Vec<_>>()
+                .serialize(serializer)
+        }
+
+        #[cfg(feature = "zerocopy")]
+        {
+            if !serializer.is_human_readable() {
+                return zerocopy::serialize_data(array, dtype, serializer);
+            }
+        }
+
+        if let DataEncoding::Chunked(chunk) = self.encoding {
+            return chunked::serialize_data(array, dtype, chunk, serializer);
+        }
 
-                match dtype.0 {
-                    DType::F32 => Ok(De(get_array::<f32>(seq.next_element()?, &dim.0))),
-                    DType::F64 => Ok(De(get_array::<f64>(seq.next_element()?, &dim.0))),
-                    DType::S16 => Ok(De(get_array::<i16>(seq.next_element()?, &dim.0))),
-                    DType::S32 => Ok(De(get_array::<i32>(seq.next_element()?, &dim.0))),
-                    DType::S64 => Ok(De(get_array::<i64>(seq.next_element()?, &dim.0))),
-                    DType::U16 => Ok(De(get_array::<u16>(seq.next_element()?, &dim.0))),
-                    DType::U32 => Ok(De(get_array::<u32>(seq.next_element()?, &dim.0))),
-                    DType::U64 => Ok(De(get_array::<u64>(seq.next_element()?, &dim.0))),
-                    DType::B8 => Ok(De(get_array::<bool>(seq.next_element()?, &dim.0))),
-                    _ => panic!("unimplemented deserialization for complex types!"),
+        if self.encoding == DataEncoding::JsSafe {
+            match dtype {
+                DType::S64 => return js_safe::serialize_ints::<i64, S>(array, serializer),
+                DType::U64 => return js_safe::serialize_ints::<u64, S>(array, serializer),
+                _ => {}
+            }
+        }
+        if self.encoding == DataEncoding::JsonSafe {
+            match dtype {
+                DType::F32 => return json_safe::serialize_floats::<f32, S>(array, serializer),
+                DType::F64 => return json_safe::serialize_floats::<f64, S>(array, serializer),
+                DType::C32 => return json_safe::serialize_complex::<f32, S>(array, serializer),
+                DType::C64 => return json_safe::serialize_complex::<f64, S>(array, serializer),
+                _ => {}
+            }
+        }
+        if let DataEncoding::Rounded(decimals) = self.encoding {
+            if serializer.is_human_readable() {
+                match dtype {
+                    DType::F32 => {
+                        return rounded::serialize_floats::<f32, S>(array, decimals, serializer)
+                    }
+                    DType::F64 => {
+                        return rounded::serialize_floats::<f64, S>(array, decimals, serializer)
+                    }
+                    DType::C32 => {
+                        return rounded::serialize_complex::<f32, S>(array, decimals, serializer)
+                    }
+                    DType::C64 => {
+                        return rounded::serialize_complex::<f64, S>(array, decimals, serializer)
+                    }
+                    _ => {}
                 }
             }
         }
-        deserializer.deserialize_tuple(3, ArrayVisitor)
+
+        match dtype {
+            DType::F32 => serialize_data::<f32, S>(array, serializer),
+            DType::F64 => serialize_data::<f64, S>(array, serializer),
+            DType::S16 => serialize_data::<i16, S>(array, serializer),
+            DType::S32 => serialize_data::<i32, S>(array, serializer),
+            DType::S64 => serialize_data::<i64, S>(array, serializer),
+            DType::U8 => serialize_data::<u8, S>(array, serializer),
+            DType::U16 => serialize_data::<u16, S>(array, serializer),
+            DType::U32 => serialize_data::<u32, S>(array, serializer),
+            DType::U64 => serialize_data::<u64, S>(array, serializer),
+            DType::B8 => serialize_data::<bool, S>(array, serializer),
+            DType::C32 => serialize_complex_data::<f32, S>(array, serializer),
+            DType::C64 => serialize_complex_data::<f64, S>(array, serializer),
+        }
+    }
+}
+
+/// Serializing an `Array` evaluates it first with `Array::eval`, running any
+/// pending JIT operations. Errors raised by arrayfire during evaluation are
+/// returned as serialization errors. The data is then always read in
+/// column-major order, so logically equal arrays serialize identically whether
+/// they were created from host data or by a fused expression.
+///
+/// All dtypes of the `arrayfire` bindings are supported, complex ones as
+/// `(re, im)` pairs. The dtype is read before any data is copied to the host,
+/// so an array of a dtype unknown to the bindings fails early with
+/// `Error::InvalidDType`.
+///
+/// Views into another array, e.g. as returned by `arrayfire::index`, are
+/// serialized by their logical values; their offset and strides are not part
+/// of the representation. Deserializing yields a standalone array with zero
+/// offset holding the same values as the view.
+impl<'a> Serialize for Ser<'a, Array> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_array(self.0, serializer, DataEncoding::Plain)
+    }
+}
+
+/// Serializes the referenced array exactly like `Ser<Array>`, so generic code
+/// that ends up with a `&&Array`, e.g. when iterating over a `Vec<&Array>`,
+/// can wrap it in `Ser::new` directly.
+impl<'a, 'b> Serialize for Ser<'a, &'b Array> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_array(self.0, serializer, DataEncoding::Plain)
+    }
+}
+
+impl<'a, 'b> Serialize for Ser<'a, &'b mut Array> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_array(self.0, serializer, DataEncoding::Plain)
     }
 }
+
+/// Serializes `array` in the `(version, dtype, dims, data)` tuple form.
+fn serialize_array<S>(
+    array: &Array,
+    serializer: S,
+    encoding: DataEncoding,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    backend_call(|| array.eval()).map_err(ser::Error::custom)?;
+    let dim = array.dims();
+    let dtype = array_dtype(array).map_err(ser::Error::custom)?;
+
+    #[cfg(feature = "logging")]
+    logging::log_array("serializing", dtype, dim.elements());
+
+    // empty arrays carry no data element
+    let empty = dim.elements() == 0;
+
+    let mut tup = serializer.serialize_tuple(if empty { 3 } else { 4 })?;
+    tup.serialize_element(&FORMAT_VERSION)?;
+    tup.serialize_element(&Ser::new(&dtype))?;
+    tup.serialize_element(&Ser::new(&dim))?;
+    if !empty {
+        tup.serialize_element(&ArrayData { array, encoding })?;
+    }
+    tup.end()
+}
+
+/// Serializes an `Array` as a struct with named `version`, `dtype`, `dims` and `data` fields
+/// when the serializer is human readable, otherwise in the compact tuple form
+/// used by `serialize`. Like `serialize`, this evaluates the array first.
+///
+/// Both forms are accepted by `deserialize`, the struct form with its fields
+/// in any order. As in the tuple form, the `data` field is omitted for empty
+/// arrays.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate serde;
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct MyStruct {
+///     #[serde(serialize_with = "arrayfire_serde::serialize_human")]
+///     #[serde(deserialize_with = "arrayfire_serde::deserialize")]
+///     tensor: arrayfire::Array,
+/// }
+/// # fn main() {}
+/// ```
+pub fn serialize_human<S>(array: &Array, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if !serializer.is_human_readable() {
+        return Ser::new(array).serialize(serializer);
+    }
+    backend_call(|| array.eval()).map_err(ser::Error::custom)?;
+
+    let dim = array.dims();
+    let dtype = array_dtype(array).map_err(ser::Error::custom)?;
+
+    #[cfg(feature = "logging")]
+    logging::log_array("serializing", dtype, dim.elements());
+
+    let empty = dim.elements() == 0;
+
+    let mut state = serializer.serialize_struct("Array", if empty { 3 } else { 4 })?;
+    state.serialize_field("version", &FORMAT_VERSION)?;
+    state.serialize_field("dtype", &Ser::new(&dtype))?;
+    state.serialize_field("dims", &Ser::new(&dim))?;
+    if !empty {
+        state.serialize_field(
+            "data",
+            &ArrayData {
+                array,
+                encoding: DataEncoding::Plain,
+            },
+        )?;
+    }
+    state.end()
+}
+
+fn check_length<E: de::Error>(len: usize, dim: &Dim4) -> Result<(), E> {
+    let expected = element_count(dim).map_err(E::custom)?;
+    if len != expected {
+        return Err(E::custom(Error::LengthMismatch {
+            expected,
+            found: len,
+        }));
+    }
+    Ok(())
+}
+
+/// Fails with `Error::DTypeMismatch` unless `found` is the `expected` dtype, if any.
+fn check_dtype(found: DType, expected: Option<DType>) -> Result<(), Error> {
+    match expected {
+        Some(expected) if expected != found => Err(Error::DTypeMismatch { expected, found }),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the number of elements in `dim` as a `usize`.
+///
+/// Unlike `Dim4::elements` the product is computed with overflow checks, since
+/// dims come from untrusted input. Fails with `Error::TooLarge` if the count
+/// overflows `u64`, or `usize` on 32-bit targets.
+fn element_count(dim: &Dim4) -> Result<usize, Error> {
+    let dims = *dim.get();
+    dims.iter()
+        .try_fold(1u64, |acc, &d| acc.checked_mul(d))
+        .and_then(|elements| usize::try_from(elements).ok())
+        .ok_or(Error::TooLarge {
+            dims,
+            max: usize::MAX as u64,
+        })
+}
+
+/// Fails with `Error::TooLarge` if `dim` holds more than `max` elements, if any,
+/// or more than fit in a `usize`.
+///
+/// Deserializers call this right after reading the dims, before `Dim4::elements`
+/// can overflow on them.
+fn check_size(dim: &Dim4, max: Option<u64>) -> Result<(), Error> {
+    let elements = element_count(dim)? as u64;
+    match max {
+        Some(max) if elements > max => Err(Error::TooLarge {
+            dims: *dim.get(),
+            max,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Private seed deserializing the element data of an `Array` with known dtype and dims.
+struct ArrayDataSeed {
+    dtype: DType,
+    dim: Dim4,
+    encoding: DataEncoding,
+}
+
+impl<'de> DeserializeSeed<'de> for ArrayDataSeed {
+    type Value = Array;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        fn get_array<'de, T, D>(deserializer: D, dim: &Dim4) -> Result<Array, D::Error>
+        where
+            T: HasAfEnum + Deserialize<'de>,
+            D: Deserializer<'de>,
+        {
+            let data: Vec<T> = Vec::deserialize(deserializer)?;
+            check_length::<D::Error>(data.len(), dim)?;
+            new_array(data.as_slice(), *dim).map_err(de::Error::custom)
+        }
+
+        fn get_complex_array<'de, T, D>(deserializer: D, dim: &Dim4) -> Result<Array, D::Error>
+        where
+            T: Deserialize<'de>,
+            Complex<T>: HasAfEnum,
+            D: Deserializer<'de>,
+        {
+            let data: Vec<Complex<T>> = Vec::<(T, T)>::deserialize(deserializer)?
+                .into_iter()
+                .map(|(re, im)| Complex { re, im })
+                .collect();
+            check_length::<D::Error>(data.len(), dim)?;
+            new_array(data.as_slice(), *dim).map_err(de::Error::custom)
+        }
+
+        // bytes are borrowed from the input where the format allows it,
+        // instead of being collected into a `Vec` first
+        fn get_u8_array<'de, D>(deserializer: D, dim: &Dim4) -> Result<Array, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let data = packed::CowBytes::deserialize(deserializer)?;
+            check_length::<D::Error>(data.0.len(), dim)?;
+            new_array(&data.0, *dim).map_err(de::Error::custom)
+        }
+
+        #[cfg(feature = "zerocopy")]
+        {
+            if !deserializer.is_human_readable() {
+                let bytes = packed::ByteBuf::deserialize(deserializer)?;
+                return zerocopy::build_array(self.dtype, self.dim, &bytes.0)
+                    .map_err(de::Error::custom);
+            }
+        }
+
+        let dim = &self.dim;
+        if self.encoding == DataEncoding::JsSafe {
+            match self.dtype {
+                DType::S64 => return js_safe::get_array::<i64, D>(deserializer, dim),
+                DType::U64 => return js_safe::get_array::<u64, D>(deserializer, dim),
+                _ => {}
+            }
+        }
+        if self.encoding == DataEncoding::JsonSafe {
+            match self.dtype {
+                DType::F32 => return json_safe::get_array::<f32, D>(deserializer, dim),
+                DType::F64 => return json_safe::get_array::<f64, D>(deserializer, dim),
+                DType::C32 => return json_safe::get_complex_array::<f32, D>(deserializer, dim),
+                DType::C64 => return json_safe::get_complex_array::<f64, D>(deserializer, dim),
+                _ => {}
+            }
+        }
+        match self.dtype {
+            DType::F32 => get_array::<f32, D>(deserializer, dim),
+            DType::F64 => get_array::<f64, D>(deserializer, dim),
+            DType::S16 => get_array::<i16, D>(deserializer, dim),
+            DType::S32 => get_array::<i32, D>(deserializer, dim),
+            DType::S64 => get_array::<i64, D>(deserializer, dim),
+            DType::U8 => get_u8_array::<D>(deserializer, dim),
+            DType::U16 => get_array::<u16, D>(deserializer, dim),
+            DType::U32 => get_array::<u32, D>(deserializer, dim),
+            DType::U64 => get_array::<u64, D>(deserializer, dim),
+            DType::B8 => get_array::<bool, D>(deserializer, dim),
+            DType::C32 => get_complex_array::<f32, D>(deserializer, dim),
+            DType::C64 => get_complex_array::<f64, D>(deserializer, dim),
+        }
+    }
+}
+
+extern "C" {
+    fn af_create_handle(out: *mut i64, ndims: c_uint, dims: *const i64, aftype: c_int) -> c_int;
+    fn af_get_type(out: *mut c_int, arr: i64) -> c_int;
+}
+
+/// Every `DType`, for mapping arrayfire's `af_dtype` values back to the enum.
+const DTYPES: [DType; 12] = [
+    DType::F32,
+    DType::C32,
+    DType::F64,
+    DType::C64,
+    DType::B8,
+    DType::S32,
+    DType::U32,
+    DType::U8,
+    DType::S64,
+    DType::U64,
+    DType::S16,
+    DType::U16,
+];
+
+/// Queries the dtype of `array`.
+///
+/// `Array::get_type` asserts on dtypes its enum conversion does not cover,
+/// `S16` and `U16` among them, so the discriminant is read directly.
+fn array_dtype(array: &Array) -> Result<DType, Error> {
+    let mut value: c_int = 0;
+    let err = unsafe { af_get_type(&mut value, array.get()) };
+    if err != 0 {
+        return Err(Error::BackendError(format!(
+            "af_get_type failed with error code {}",
+            err
+        )));
+    }
+    dtype_from_discriminant(value)
+}
+
+/// Looks up the dtype whose `af_dtype` discriminant is `value`.
+fn dtype_from_discriminant(value: c_int) -> Result<DType, Error> {
+    DTYPES
+        .iter()
+        .cloned()
+        .find(|&dtype| dtype as c_int == value)
+        .ok_or(Error::InvalidDType(value as u64))
+}
+
+/// Constructs an array with zero elements without going through element data.
+///
+/// `Array::new` and `Array::new_empty` derive the rank from `Dim4::ndims`, which
+/// is zero for empty shapes, so the backend is asked for an empty array with all
+/// four dimensions directly.
+fn empty_array(dtype: DType, dim: Dim4) -> Result<Array, Error> {
+    let mut dims = [0i64; 4];
+    for (d, &value) in dims.iter_mut().zip(dim.get()) {
+        *d = i64::try_from(value).map_err(|_| {
+            Error::BackendError(format!("array dims {:?} are out of range", dim.get()))
+        })?;
+    }
+    let mut handle: i64 = 0;
+    let err = unsafe { af_create_handle(&mut handle, 4, dims.as_ptr(), dtype as c_int) };
+    if err != 0 {
+        return Err(Error::BackendError(format!(
+            "af_create_handle failed with error code {}",
+            err
+        )));
+    }
+    Ok(Array::from(handle))
+}
+
+/// Creates an array from `data` with `Array::new`, returning a failure of the
+/// backend, e.g. when out of device memory, as `Error::BackendError`.
+fn new_array<T: HasAfEnum>(data: &[T], dim: Dim4) -> Result<Array, Error> {
+    backend_call(|| Array::new(data, dim))
+}
+
+/// Field names of the human readable `Array` representation.
+#[derive(Deserialize, PartialEq)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum ArrayField {
+    Version,
+    Dtype,
+    Dims,
+    Data,
+}
+
+impl ArrayField {
+    fn name(&self) -> &'static str {
+        match *self {
+            ArrayField::Version => "version",
+            ArrayField::Dtype => "dtype",
+            ArrayField::Dims => "dims",
+            ArrayField::Data => "data",
+        }
+    }
+}
+
+/// Field names of the `Array` representation, in serialization order.
+const ARRAY_FIELDS: &[&str] = &["version", "dtype", "dims", "data"];
+
+/// Private visitor for both the tuple and the struct form of an `Array`.
+///
+/// With `max_elements` set, arrays declaring more elements fail before any data is read,
+/// and with `dtype` set so do arrays of any other dtype. With `strict` set, the
+/// tuple form fails on any element following the data. The tuple of an empty
+/// array has no data element, so it is only probed with `probe_empty` also set:
+/// formats that are not self-describing would read the probe from whatever
+/// follows the array, or fail outright.
+struct ArrayVisitor {
+    encoding: DataEncoding,
+    max_elements: Option<u64>,
+    dtype: Option<DType>,
+    strict: bool,
+    probe_empty: bool,
+}
+
+impl<'de> Visitor<'de> for ArrayVisitor {
+    type Value = Array;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "struct ArrayStruct")
+    }
+
+    fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+    where
+        V: SeqAccess<'de>,
+    {
+        let version: u16 = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::custom("expected 4 elements, found fewer"))?;
+        check_version(version)?;
+        let dtype: De<DType> = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::custom("expected 4 elements, found fewer"))?;
+        let dim: De<Dim4> = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::custom("expected 4 elements, found fewer"))?;
+        check_dtype(dtype.0, self.dtype).map_err(de::Error::custom)?;
+        check_size(&dim.0, self.max_elements).map_err(de::Error::custom)?;
+        let empty = dim.0.elements() == 0;
+        let array = if empty {
+            empty_array(dtype.0, dim.0).map_err(de::Error::custom)?
+        } else {
+            let seed = ArrayDataSeed {
+                dtype: dtype.0,
+                dim: dim.0,
+                encoding: self.encoding,
+            };
+            seq.next_element_seed(seed)?
+                .ok_or_else(|| de::Error::custom("expected 4 elements, found fewer"))?
+        };
+        let probe = self.strict && (!empty || self.probe_empty);
+        if probe && seq.next_element::<de::IgnoredAny>()?.is_some() {
+            return Err(de::Error::custom(format_args!(
+                "expected {} elements, found more",
+                if empty { 3 } else { 4 }
+            )));
+        }
+        #[cfg(feature = "logging")]
+        logging::log_array("deserialized", dtype.0, dim.0.elements());
+        Ok(array)
+    }
+
+    fn visit_map<V>(self, map: V) -> Result<Self::Value, V::Error>
+    where
+        V: MapAccess<'de>,
+    {
+        let (encoding, expected) = (self.encoding, self.dtype);
+        let read_dtype = |map: &mut V| {
+            let dtype = map.next_value::<De<DType>>()?.0;
+            check_dtype(dtype, expected).map_err(de::Error::custom)?;
+            Ok(dtype)
+        };
+        let seed = |dtype, dim| ArrayDataSeed {
+            dtype,
+            dim,
+            encoding,
+        };
+        let fields = visit_array_map(map, self.max_elements, read_dtype, seed)?;
+        #[cfg(feature = "logging")]
+        logging::log_array("deserialized", fields.dtype, fields.dim.elements());
+        match fields.data {
+            Some(array) => Ok(array),
+            None => empty_array(fields.dtype, fields.dim).map_err(de::Error::custom),
+        }
+    }
+}
+
+/// The fields of the struct form of an `Array`, as read by `visit_array_map`.
+struct ArrayMap<T> {
+    dtype: DType,
+    dim: Dim4,
+    /// The data read with the seed, `None` for arrays without elements.
+    data: Option<T>,
+}
+
+/// Reads the struct form of an `Array` from `map`, with the fields in any order.
+///
+/// The dtype is read with `read_dtype`, and the dims are checked against
+/// `max_elements` as soon as they are read. `data` is read directly with the
+/// seed returned by `seed` when `dtype` and `dims` precede it, and is otherwise
+/// buffered until they are known. The data of arrays without elements is
+/// skipped, or checked to be empty when buffered.
+fn visit_array_map<'de, V, R, F, S>(
+    mut map: V,
+    max_elements: Option<u64>,
+    mut read_dtype: R,
+    mut seed: F,
+) -> Result<ArrayMap<S::Value>, V::Error>
+where
+    V: MapAccess<'de>,
+    R: FnMut(&mut V) -> Result<DType, V::Error>,
+    F: FnMut(DType, Dim4) -> S,
+    S: DeserializeSeed<'de>,
+{
+    let mut version = false;
+    let mut dtype: Option<DType> = None;
+    let mut dim: Option<Dim4> = None;
+    let mut data: Option<Option<S::Value>> = None;
+    let mut buffered: Option<Vec<buffer::Element>> = None;
+    while let Some(key) = map.next_key::<ArrayField>()? {
+        let seen = match key {
+            ArrayField::Version => version,
+            ArrayField::Dtype => dtype.is_some(),
+            ArrayField::Dims => dim.is_some(),
+            ArrayField::Data => data.is_some() || buffered.is_some(),
+        };
+        if seen {
+            return Err(de::Error::duplicate_field(key.name()));
+        }
+        match key {
+            ArrayField::Version => {
+                check_version(map.next_value()?)?;
+                version = true;
+            }
+            ArrayField::Dtype => dtype = Some(read_dtype(&mut map)?),
+            ArrayField::Dims => {
+                let value = map.next_value::<De<Dim4>>()?.0;
+                check_size(&value, max_elements).map_err(de::Error::custom)?;
+                dim = Some(value);
+            }
+            ArrayField::Data => match (dtype, dim) {
+                (Some(_), Some(dim)) if dim.elements() == 0 => {
+                    map.next_value::<de::IgnoredAny>()?;
+                    data = Some(None);
+                }
+                (Some(dtype), Some(dim)) => {
+                    data = Some(Some(map.next_value_seed(seed(dtype, dim))?));
+                }
+                _ => {
+                    buffered = Some(map.next_value_seed(buffer::BufferSeed { max_elements })?);
+                }
+            },
+        }
+    }
+    if !version {
+        return Err(de::Error::missing_field(ArrayField::Version.name()));
+    }
+    let dtype = dtype.ok_or_else(|| de::Error::missing_field(ArrayField::Dtype.name()))?;
+    let dim = dim.ok_or_else(|| de::Error::missing_field(ArrayField::Dims.name()))?;
+    if let Some(data) = data {
+        return Ok(ArrayMap { dtype, dim, data });
+    }
+    // `data` is omitted for empty arrays
+    if dim.elements() == 0 {
+        check_length::<V::Error>(buffered.map_or(0, |elements| elements.len()), &dim)?;
+        return Ok(ArrayMap {
+            dtype,
+            dim,
+            data: None,
+        });
+    }
+    let elements = buffered.ok_or_else(|| de::Error::missing_field(ArrayField::Data.name()))?;
+    let data = seed(dtype, dim).deserialize(SeqDeserializer::new(elements.into_iter()))?;
+    Ok(ArrayMap {
+        dtype,
+        dim,
+        data: Some(data),
+    })
+}
+
+impl<'de> Deserialize<'de> for De<Array> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer
+            .deserialize_struct(
+                "Array",
+                ARRAY_FIELDS,
+                ArrayVisitor {
+                    encoding: DataEncoding::Plain,
+                    max_elements: None,
+                    dtype: None,
+                    strict: false,
+                    probe_empty: false,
+                },
+            )
+            .map(De)
+    }
+
+    fn deserialize_in_place<D>(deserializer: D, place: &mut Self) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        in_place::deserialize_in_place(deserializer, &mut place.0)
+    }
+}
+
+/// Casts `array` to `dtype` with `Array::cast`.
+fn cast_array(array: Array, dtype: DType) -> Result<Array, Error> {
+    if array_dtype(&array)? == dtype {
+        return Ok(array);
+    }
+    if array.elements() == 0 {
+        return empty_array(dtype, array.dims());
+    }
+    backend_call(|| match dtype {
+        DType::F32 => array.cast::<f32>(),
+        DType::C32 => array.cast::<Complex<f32>>(),
+        DType::F64 => array.cast::<f64>(),
+        DType::C64 => array.cast::<Complex<f64>>(),
+        DType::B8 => array.cast::<bool>(),
+        DType::S32 => array.cast::<i32>(),
+        DType::U32 => array.cast::<u32>(),
+        DType::U8 => array.cast::<u8>(),
+        DType::S64 => array.cast::<i64>(),
+        DType::U64 => array.cast::<u64>(),
+        DType::S16 => array.cast::<i16>(),
+        DType::U16 => array.cast::<u16>(),
+    })
+}
+
+/// Deserializes an `Array` written by `serialize` and casts it to `dtype`.
+///
+/// The data is read with the stored dtype; only the returned array has type `dtype`.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate serde;
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// fn deserialize_f32<'de, D>(deserializer: D) -> Result<arrayfire::Array, D::Error>
+/// where
+///     D: serde::Deserializer<'de>,
+/// {
+///     arrayfire_serde::deserialize_as(arrayfire::DType::F32, deserializer)
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct MyStruct {
+///     #[serde(deserialize_with = "deserialize_f32")]
+///     tensor: arrayfire::Array,
+/// }
+/// # fn main() {}
+/// ```
+pub fn deserialize_as<'de, D>(dtype: DType, deserializer: D) -> Result<Array, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let array = De::<Array>::deserialize(deserializer)?.into_inner();
+    cast_array(array, dtype).map_err(de::Error::custom)
+}
+
+/// Inclusive value range of an integer or boolean dtype, `None` for floating point
+/// and complex dtypes.
+fn integer_range(dtype: DType) -> Option<(i128, i128)> {
+    match dtype {
+        DType::B8 => Some((0, 1)),
+        DType::U8 => Some((0, u8::MAX as i128)),
+        DType::S16 => Some((i16::MIN as i128, i16::MAX as i128)),
+        DType::U16 => Some((0, u16::MAX as i128)),
+        DType::S32 => Some((i32::MIN as i128, i32::MAX as i128)),
+        DType::U32 => Some((0, u32::MAX as i128)),
+        DType::S64 => Some((i64::MIN as i128, i64::MAX as i128)),
+        DType::U64 => Some((0, u64::MAX as i128)),
+        _ => None,
+    }
+}
+
+/// Whether arrayfire would promote `from` to `to` when mixing the two, i.e.
+/// whether `to` is at least as wide as `from`.
+///
+/// Any real dtype promotes to a floating point dtype of at least its precision
+/// and to the complex dtype of that precision. Integers promote to both float
+/// dtypes, as in arrayfire, and to integer dtypes holding their whole range.
+fn promotes_to(from: DType, to: DType) -> bool {
+    if from == to {
+        return true;
+    }
+    match to {
+        DType::C64 => true,
+        DType::C32 => from != DType::F64 && from != DType::C64,
+        DType::F64 => from != DType::C32 && from != DType::C64,
+        DType::F32 => integer_range(from).is_some(),
+        _ => match (integer_range(from), integer_range(to)) {
+            (Some((from_min, from_max)), Some((to_min, to_max))) => {
+                to_min <= from_min && from_max <= to_max
+            }
+            _ => false,
+        },
+    }
+}
+
+/// Deserializes an `Array` written by `serialize` and promotes it to `target`.
+///
+/// Unlike `deserialize_as`, only widening conversions are accepted, so that
+/// arrays of mixed precision can be loaded into a common dtype without silently
+/// losing range: e.g. `S16`, `U8` or `F32` promote to `F32` or `F64`, but `F64`
+/// does not promote to `F32` nor `S32` to `S16`. Fails with
+/// `Error::DTypeMismatch` for a stored dtype that does not promote to `target`.
+///
+/// Integers up to 16 bits are represented exactly by `F32`, and up to 32 bits by
+/// `F64`; wider integers promote to floats as in arrayfire, with rounding.
+pub fn deserialize_promote<'de, D>(target: DType, deserializer: D) -> Result<Array, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let array = De::<Array>::deserialize(deserializer)?.into_inner();
+    let found = array_dtype(&array).map_err(de::Error::custom)?;
+    if !promotes_to(found, target) {
+        return Err(de::Error::custom(Error::DTypeMismatch {
+            expected: target,
+            found,
+        }));
+    }
+    cast_array(array, target).map_err(de::Error::custom)
+}
+
+/// Deserializes the element data of an `Array` of the given `dtype` and `dims`.
+///
+/// Only the data is read, as a plain sequence of elements in column-major
+/// order like the `data` field of `serialize`; the dtype and dims come from
+/// elsewhere, e.g. a separate header. The number of elements must match `dims`.
+///
+/// ```rust
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+/// extern crate serde;
+///
+/// use arrayfire::{Array, DType, Dim4};
+///
+/// fn deserialize_data<'de, D>(header: (DType, Dim4), deserializer: D) -> Result<Array, D::Error>
+/// where
+///     D: serde::Deserializer<'de>,
+/// {
+///     arrayfire_serde::deserialize_flat(header.0, header.1, deserializer)
+/// }
+/// # fn main() {}
+/// ```
+pub fn deserialize_flat<'de, D>(
+    dtype: DType,
+    dims: Dim4,
+    deserializer: D,
+) -> Result<Array, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    check_size(&dims, None).map_err(de::Error::custom)?;
+    if dims.elements() == 0 {
+        let data = Vec::<de::IgnoredAny>::deserialize(deserializer)?;
+        check_length::<D::Error>(data.len(), &dims)?;
+        return empty_array(dtype, dims).map_err(de::Error::custom);
+    }
+    ArrayDataSeed {
+        dtype,
+        dim: dims,
+        encoding: DataEncoding::Plain,
+    }
+    .deserialize(deserializer)
+}
+
+/// Deserializes an `Array` written by `serialize`, failing with `Error::TooLarge`
+/// when its dims declare more than `max_elements` elements.
+///
+/// The limit is checked before the data is read or allocated, so untrusted
+/// input cannot request arbitrarily large allocations.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate serde;
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// fn deserialize_small<'de, D>(deserializer: D) -> Result<arrayfire::Array, D::Error>
+/// where
+///     D: serde::Deserializer<'de>,
+/// {
+///     arrayfire_serde::deserialize_limited(1 << 20, deserializer)
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct MyStruct {
+///     #[serde(deserialize_with = "deserialize_small")]
+///     tensor: arrayfire::Array,
+/// }
+/// # fn main() {}
+/// ```
+pub fn deserialize_limited<'de, D>(max_elements: u64, deserializer: D) -> Result<Array, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let visitor = ArrayVisitor {
+        encoding: DataEncoding::Plain,
+        max_elements: Some(max_elements),
+        dtype: None,
+        strict: false,
+        probe_empty: false,
+    };
+    deserializer.deserialize_struct("Array", ARRAY_FIELDS, visitor)
+}
+
+/// Deserializes an `Array` written by `serialize`, failing if the tuple holds
+/// elements after the data.
+///
+/// `deserialize` stops reading after the data, so in formats that do not check
+/// the length of a tuple themselves, elements appended by a newer writer are
+/// silently ignored. Use this to reject them instead. Unknown fields of the
+/// struct form are rejected either way.
+///
+/// The tuple of an empty array ends after the dims, and only human readable
+/// formats are probed for elements following them.
+pub fn deserialize_strict<'de, D>(deserializer: D) -> Result<Array, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let visitor = ArrayVisitor {
+        encoding: DataEncoding::Plain,
+        max_elements: None,
+        dtype: None,
+        strict: true,
+        probe_empty: deserializer.is_human_readable(),
+    };
+    deserializer.deserialize_struct("Array", ARRAY_FIELDS, visitor)
+}
+
+/// Deserializes only the dtype and dimensions of an `Array` written by `serialize`
+/// or `serialize_human`, without allocating its data.
+///
+/// Self-describing formats skip over the data with `serde::de::IgnoredAny`, or
+/// buffer it when the struct form lists it before `dtype` and `dims`.
+/// Other formats, such as `bincode`, stop reading after the dimensions and leave
+/// the data unread in the input.
+pub fn deserialize_header<'de, D>(deserializer: D) -> Result<(DType, Dim4), D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct HeaderVisitor;
+
+    impl<'de> Visitor<'de> for HeaderVisitor {
+        type Value = (DType, Dim4);
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "struct ArrayStruct")
+        }
+
+        fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+        where
+            V: SeqAccess<'de>,
+        {
+            let version: u16 = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 4 elements, found fewer"))?;
+            check_version(version)?;
+            let dtype: De<DType> = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 4 elements, found fewer"))?;
+            let dim: De<Dim4> = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 4 elements, found fewer"))?;
+            while seq.next_element::<de::IgnoredAny>()?.is_some() {}
+            Ok((dtype.0, dim.0))
+        }
+
+        fn visit_map<V>(self, map: V) -> Result<Self::Value, V::Error>
+        where
+            V: MapAccess<'de>,
+        {
+            let read_dtype = |map: &mut V| map.next_value::<De<DType>>().map(|dtype| dtype.0);
+            let skip = |_, _| PhantomData::<de::IgnoredAny>;
+            let fields = visit_array_map(map, None, read_dtype, skip)?;
+            Ok((fields.dtype, fields.dim))
+        }
+    }
+
+    // Only the header fields are requested, so formats that rely on the
+    // declared length never reach the data element.
+    const FIELDS: &[&str] = &["version", "dtype", "dims"];
+    deserializer.deserialize_struct("Array", FIELDS, HeaderVisitor)
+}
+
+/// Serializes an `Array` together with the currently active arrayfire backend,
+/// as a `(backend, array)` tuple.
+///
+/// The array itself uses the same representation as `serialize`.
+/// Use `deserialize_full` to read it back.
+pub fn serialize_full<S>(array: &Array, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let backend = arrayfire::get_active_backend();
+
+    let mut tup = serializer.serialize_tuple(2)?;
+    tup.serialize_element(&Ser::new(&backend))?;
+    tup.serialize_element(&Ser::new(array))?;
+    tup.end()
+}
+
+/// An `Array` read by `deserialize_full` along with the backend it was serialized on.
+pub struct FullArray {
+    pub array: Array,
+    pub backend: Backend,
+}
+
+/// Deserializes an `Array` written by `serialize_full`.
+///
+/// The array is created on the active backend of this process;
+/// the recorded backend is informational only.
+pub fn deserialize_full<'de, D>(deserializer: D) -> Result<FullArray, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct FullArrayVisitor;
+
+    impl<'de> Visitor<'de> for FullArrayVisitor {
+        type Value = FullArray;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "tuple as a seq of 2 elements")
+        }
+
+        fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+        where
+            V: SeqAccess<'de>,
+        {
+            let backend: De<Backend> = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 2 elements, found fewer"))?;
+            let array: De<Array> = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 2 elements, found fewer"))?;
+            Ok(FullArray {
+                array: array.0,
+                backend: backend.0,
+            })
+        }
+    }
+
+    deserializer.deserialize_tuple(2, FullArrayVisitor)
+}
+
+/// Serializes an `Array` into a byte vector with `bincode`.
+///
+/// Requires the `bincode` feature.
+///
+/// ```rust
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// # fn main() {
+/// let array = arrayfire::Array::new(&[1.0f32, 2.0], arrayfire::Dim4::new(&[2, 1, 1, 1]));
+/// let bytes = arrayfire_serde::to_bytes(&array).unwrap();
+/// let array = arrayfire_serde::from_bytes(&bytes).unwrap();
+/// # }
+/// ```
+#[cfg(feature = "bincode")]
+pub fn to_bytes(array: &Array) -> Result<Vec<u8>, Error> {
+    bincode::serialize(&Ser::new(array)).map_err(|e| Error::Format(e.to_string()))
+}
+
+/// Deserializes an `Array` from bytes produced by `to_bytes`.
+///
+/// Requires the `bincode` feature.
+#[cfg(feature = "bincode")]
+pub fn from_bytes(bytes: &[u8]) -> Result<Array, Error> {
+    bincode::deserialize::<De<Array>>(bytes)
+        .map(De::into_inner)
+        .map_err(|e| Error::Format(e.to_string()))
+}
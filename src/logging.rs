@@ -0,0 +1,31 @@
+//! Logging of large array transfers with the `log` crate.
+
+use arrayfire::DType;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use log::info;
+
+use super::packed::dtype_size;
+
+/// Host size in bytes above which transfers are logged.
+static THRESHOLD: AtomicUsize = AtomicUsize::new(1 << 20);
+
+/// Sets the host size in bytes above which arrays passing through `serialize`
+/// and `deserialize` are logged. Defaults to 1 MiB.
+///
+/// Requires the `logging` feature. The records are logged at the `Info` level
+/// with the dtype, element count and size, e.g.
+/// `serializing F32 array of 1048576 elements (4194304 bytes)`.
+pub fn set_log_threshold(bytes: usize) {
+    THRESHOLD.store(bytes, Ordering::Relaxed);
+}
+
+/// Logs the transfer of an array of `elements` values of `dtype` if it is above the threshold.
+pub(crate) fn log_array(action: &str, dtype: DType, elements: u64) {
+    let bytes = elements.saturating_mul(dtype_size(dtype) as u64);
+    if bytes > THRESHOLD.load(Ordering::Relaxed) as u64 {
+        info!(
+            "{} {:?} array of {} elements ({} bytes)",
+            action, dtype, elements, bytes
+        );
+    }
+}
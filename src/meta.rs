@@ -0,0 +1,113 @@
+//! Metadata-only `Array` representation, without the element data.
+
+use arrayfire::{Array, DType, Dim4};
+use core::fmt;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::{self, SerializeTuple};
+use serde::{Deserializer, Serializer};
+
+use super::{array_dtype, element_count, De, Error, Ser};
+
+/// The dtype and dimensions of an `Array`, as written by `serialize_meta`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ArrayMeta {
+    pub dtype: DType,
+    pub dims: Dim4,
+}
+
+impl ArrayMeta {
+    /// Reads the dtype and dimensions of `array` without touching its data.
+    pub fn of(array: &Array) -> Result<ArrayMeta, Error> {
+        Ok(ArrayMeta {
+            dtype: array_dtype(array)?,
+            dims: array.dims(),
+        })
+    }
+
+    /// Number of elements described by `dims`.
+    pub fn elements(&self) -> Result<usize, Error> {
+        element_count(&self.dims)
+    }
+}
+
+/// Serializes the dtype and dimensions of an `Array` as a
+/// `(dtype, dims, element_count)` tuple, without its data.
+///
+/// Neither evaluates the array nor copies anything from the device, so this is
+/// cheap enough for dry runs, e.g. estimating the size of a checkpoint.
+/// `element_count` is redundant with `dims` and is checked against them by
+/// `deserialize_meta`.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate serde;
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// #[derive(Serialize)]
+/// struct Plan {
+///     #[serde(serialize_with = "arrayfire_serde::serialize_meta")]
+///     input: arrayfire::Array,
+/// }
+/// # fn main() {}
+/// ```
+pub fn serialize_meta<S>(array: &Array, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let meta = ArrayMeta::of(array).map_err(ser::Error::custom)?;
+    let elements = meta.elements().map_err(ser::Error::custom)?;
+
+    let mut tup = serializer.serialize_tuple(3)?;
+    tup.serialize_element(&Ser::new(&meta.dtype))?;
+    tup.serialize_element(&Ser::new(&meta.dims))?;
+    tup.serialize_element(&(elements as u64))?;
+    tup.end()
+}
+
+/// Deserializes an `ArrayMeta` written by `serialize_meta`.
+pub fn deserialize_meta<'de, D>(deserializer: D) -> Result<ArrayMeta, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct MetaVisitor;
+
+    impl<'de> Visitor<'de> for MetaVisitor {
+        type Value = ArrayMeta;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "tuple as a seq of 3 elements")
+        }
+
+        fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+        where
+            V: SeqAccess<'de>,
+        {
+            let dtype: De<DType> = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 3 elements, found fewer"))?;
+            let dims: De<Dim4> = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 3 elements, found fewer"))?;
+            let elements: u64 = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 3 elements, found fewer"))?;
+            let meta = ArrayMeta {
+                dtype: dtype.0,
+                dims: dims.0,
+            };
+            let expected = meta.elements().map_err(de::Error::custom)?;
+            if elements != expected as u64 {
+                return Err(de::Error::custom(format_args!(
+                    "element count {} does not match dims {:?}",
+                    elements,
+                    meta.dims.get()
+                )));
+            }
+            Ok(meta)
+        }
+    }
+
+    deserializer.deserialize_tuple(3, MetaVisitor)
+}
@@ -0,0 +1,75 @@
+//! MessagePack reading and writing of `Array` values with `rmp_serde`.
+//!
+//! Requires the `rmp-serde` feature.
+//!
+//! Arrays are written as MessagePack arrays in the tuple form of
+//! `arrayfire_serde::serialize`, with the dtype as its `u8` tag and the
+//! dimensions as unsigned integers in their shortest encoding. With `binary`
+//! set, the data follows the form of `serialize_packed` instead and is written
+//! as a single MessagePack binary.
+//!
+//! ```rust
+//! extern crate arrayfire;
+//! extern crate arrayfire_serde;
+//!
+//! # fn main() {
+//! let array = arrayfire::Array::new(&[1.0f32, 2.0], arrayfire::Dim4::new(&[2, 1, 1, 1]));
+//! let bytes = arrayfire_serde::msgpack::to_vec(&array, true).unwrap();
+//! let array = arrayfire_serde::msgpack::from_slice(&bytes).unwrap();
+//! # }
+//! ```
+
+use arrayfire::Array;
+use rmp_serde;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{deserialize_packed, serialize_packed, Error, Ser, Serde};
+
+/// Leading byte of a MessagePack array of 5 elements, the tuple length of `serialize_packed`.
+const PACKED_MARKER: u8 = 0x95;
+
+struct Packed<'a>(&'a Array);
+
+impl<'a> Serialize for Packed<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_packed(self.0, serializer)
+    }
+}
+
+struct PackedArray(Array);
+
+impl<'de> Deserialize<'de> for PackedArray {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_packed(deserializer).map(PackedArray)
+    }
+}
+
+/// Writes `array` as MessagePack, with its data as a MessagePack binary when
+/// `binary` is set and as a MessagePack array of elements otherwise.
+///
+/// With the `zerocopy` feature, the data is a binary either way.
+pub fn to_vec(array: &Array, binary: bool) -> Result<Vec<u8>, Error> {
+    let bytes = if binary {
+        rmp_serde::to_vec(&Packed(array))
+    } else {
+        rmp_serde::to_vec(&Ser::new(array))
+    };
+    bytes.map_err(|e| Error::Format(e.to_string()))
+}
+
+/// Reads an `Array` from MessagePack written by `to_vec`, with either
+/// setting of `binary`.
+pub fn from_slice(bytes: &[u8]) -> Result<Array, Error> {
+    let array = if bytes.first() == Some(&PACKED_MARKER) {
+        rmp_serde::from_slice::<PackedArray>(bytes).map(|packed| packed.0)
+    } else {
+        rmp_serde::from_slice::<Serde<Array>>(bytes).map(Serde::into_inner)
+    };
+    array.map_err(|e| Error::Format(e.to_string()))
+}
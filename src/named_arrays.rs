@@ -0,0 +1,91 @@
+//! Serialization of maps from names to `arrayfire` types, such as the named
+//! weights of a model.
+//!
+//! Intended for use with the `serde` attribute
+//! `#[serde(with = "arrayfire_serde::named_arrays")]` on `HashMap<String, Array>`
+//! or `BTreeMap<String, Array>` fields:
+//!
+//! ```rust
+//! #[macro_use]
+//! extern crate serde_derive;
+//! extern crate serde;
+//! extern crate arrayfire;
+//! extern crate arrayfire_serde;
+//!
+//! use std::collections::HashMap;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Model {
+//!     #[serde(with = "arrayfire_serde::named_arrays")]
+//!     weights: HashMap<String, arrayfire::Array>,
+//! }
+//! # fn main() {}
+//! ```
+
+use serde::de::{MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+
+use super::{De, Ser};
+
+/// Serializes a map from names to values as a serde map, each value in the
+/// same form as `arrayfire_serde::serialize`.
+///
+/// Entries are written sorted by name, so the output does not depend on the
+/// iteration order of the map and is the same for a `HashMap` and a `BTreeMap`.
+pub fn serialize<'m, M, T, S>(map: &'m M, serializer: S) -> Result<S::Ok, S::Error>
+where
+    &'m M: IntoIterator<Item = (&'m String, &'m T)>,
+    T: 'm,
+    for<'a> Ser<'a, T>: Serialize,
+    S: Serializer,
+{
+    let mut entries: Vec<(&String, &T)> = map.into_iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut state = serializer.serialize_map(Some(entries.len()))?;
+    for (name, value) in entries {
+        state.serialize_entry(name, &Ser::new(value))?;
+    }
+    state.end()
+}
+
+/// Deserializes a map written by `serialize` into any map type, e.g. a
+/// `HashMap<String, Array>` or a `BTreeMap<String, Array>`.
+pub fn deserialize<'de, M, T, D>(deserializer: D) -> Result<M, D::Error>
+where
+    M: Default + Extend<(String, T)>,
+    De<T>: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    struct NamedVisitor<M, T>(PhantomData<(M, T)>);
+
+    impl<'de, M, T> Visitor<'de> for NamedVisitor<M, T>
+    where
+        M: Default + Extend<(String, T)>,
+        De<T>: Deserialize<'de>,
+    {
+        type Value = M;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "a map from names to arrays")
+        }
+
+        fn visit_map<V>(self, mut access: V) -> Result<Self::Value, V::Error>
+        where
+            V: MapAccess<'de>,
+        {
+            let mut map = M::default();
+            while let Some((name, value)) = access.next_entry::<String, De<T>>()? {
+                map.extend(Some((name, value.into_inner())));
+            }
+            Ok(map)
+        }
+    }
+
+    deserializer.deserialize_map(NamedVisitor(PhantomData))
+}
@@ -0,0 +1,298 @@
+//! Reading and writing of NumPy `.npy` files.
+//!
+//! Versions 1.0 to 3.0 of the format are read, with any of the dtypes that
+//! have an arrayfire counterpart, and version 1.0 is written. NumPy's `shape` becomes the dims of the
+//! array, so element `[i, j]` of a NumPy matrix is element `(i, j)` of the
+//! `Array` whichever order the data is stored in.
+
+use arrayfire::{Array, DType, Dim4};
+use std::io::{self, Read, Write};
+
+use super::binary::invalid_input;
+use super::order::reverse_axes;
+use super::packed::{
+    build_array, component_size, dtype_size, swap_bytes, swap_little_endian, with_host_bytes,
+};
+use super::{array_dtype, element_count, empty_array, get_data, new_array, Error};
+
+const MAGIC: &[u8; 6] = b"\x93NUMPY";
+
+/// The fields of a `.npy` header.
+struct NpyHeader {
+    dtype: DType,
+    big_endian: bool,
+    fortran_order: bool,
+    dim: Dim4,
+}
+
+/// Returns the raw value of `key` in the Python dict literal `header`: the
+/// contents of a quoted string, a parenthesized tuple, or a bare word.
+fn header_field<'a>(header: &'a str, key: &str) -> Result<&'a str, Error> {
+    let missing = || Error::Format(format!("missing '{}' in .npy header", key));
+    let pattern = format!("'{}':", key);
+    let start = header.find(&pattern).ok_or_else(missing)? + pattern.len();
+    let rest = header[start..].trim_start();
+    let value = if let Some(quoted) = rest.strip_prefix('\'') {
+        quoted.split('\'').next()
+    } else if rest.starts_with('(') {
+        rest.find(')').map(|end| &rest[..=end])
+    } else {
+        rest.split([',', '}']).next().map(str::trim)
+    };
+    value.ok_or_else(missing)
+}
+
+/// Maps a NumPy dtype string such as `<f4` to a `DType` and whether its data is big-endian.
+fn parse_descr(descr: &str) -> Result<(DType, bool), Error> {
+    let unsupported = || Error::Format(format!("unsupported .npy dtype '{}'", descr));
+    let (big_endian, code) = match descr.chars().next() {
+        Some('<') => (false, &descr[1..]),
+        Some('>') => (true, &descr[1..]),
+        Some('=') | Some('|') => (cfg!(target_endian = "big"), &descr[1..]),
+        _ => return Err(unsupported()),
+    };
+    let dtype = match code {
+        "f4" => DType::F32,
+        "f8" => DType::F64,
+        "c8" => DType::C32,
+        "c16" => DType::C64,
+        "b1" => DType::B8,
+        "u1" => DType::U8,
+        "i2" => DType::S16,
+        "u2" => DType::U16,
+        "i4" => DType::S32,
+        "u4" => DType::U32,
+        "i8" => DType::S64,
+        "u8" => DType::U64,
+        _ => return Err(unsupported()),
+    };
+    Ok((dtype, big_endian))
+}
+
+/// The NumPy dtype string of `dtype`, little-endian where byte order matters.
+fn descr(dtype: DType) -> &'static str {
+    match dtype {
+        DType::F32 => "<f4",
+        DType::F64 => "<f8",
+        DType::C32 => "<c8",
+        DType::C64 => "<c16",
+        DType::B8 => "|b1",
+        DType::U8 => "|u1",
+        DType::S16 => "<i2",
+        DType::U16 => "<u2",
+        DType::S32 => "<i4",
+        DType::U32 => "<u4",
+        DType::S64 => "<i8",
+        DType::U64 => "<u8",
+    }
+}
+
+/// Parses a shape tuple such as `(2, 3)` into dims, padded with ones.
+fn parse_shape(shape: &str) -> Result<Dim4, Error> {
+    let invalid = || Error::Format(format!("invalid .npy shape {}", shape));
+    let inner = shape
+        .strip_prefix('(')
+        .and_then(|s| s.strip_suffix(')'))
+        .ok_or_else(invalid)?;
+    let mut dims = [1u64; 4];
+    let parts = inner.split(',').map(str::trim).filter(|d| !d.is_empty());
+    for (ndims, d) in parts.enumerate() {
+        if ndims == 4 {
+            return Err(Error::Format(format!(
+                "a .npy shape of more than 4 dimensions is not supported: {}",
+                shape
+            )));
+        }
+        dims[ndims] = d.parse().map_err(|_| invalid())?;
+    }
+    Ok(Dim4::new(&dims))
+}
+
+fn read_header<R: Read>(r: &mut R) -> Result<NpyHeader, Error> {
+    let mut prefix = [0u8; 8];
+    r.read_exact(&mut prefix)?;
+    if &prefix[..6] != MAGIC {
+        return Err(Error::Format("not a .npy file".to_string()));
+    }
+    let header_len = match prefix[6] {
+        1 => {
+            let mut len = [0u8; 2];
+            r.read_exact(&mut len)?;
+            u64::from(u16::from_le_bytes(len))
+        }
+        2 | 3 => {
+            let mut len = [0u8; 4];
+            r.read_exact(&mut len)?;
+            u64::from(u32::from_le_bytes(len))
+        }
+        major => {
+            return Err(Error::Format(format!(
+                "unsupported .npy version {}.{}",
+                major, prefix[7]
+            )))
+        }
+    };
+
+    let mut header = Vec::new();
+    r.take(header_len).read_to_end(&mut header)?;
+    if header.len() as u64 != header_len {
+        return Err(Error::Io(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            ".npy header is truncated",
+        )));
+    }
+    let header = std::str::from_utf8(&header)
+        .map_err(|_| Error::Format(".npy header is not valid UTF-8".to_string()))?;
+
+    let (dtype, big_endian) = parse_descr(header_field(header, "descr")?)?;
+    let fortran_order = match header_field(header, "fortran_order")? {
+        "True" => true,
+        "False" => false,
+        value => {
+            return Err(Error::Format(format!(
+                "invalid .npy fortran_order {}",
+                value
+            )))
+        }
+    };
+    let dim = parse_shape(header_field(header, "shape")?)?;
+    Ok(NpyHeader {
+        dtype,
+        big_endian,
+        fortran_order,
+        dim,
+    })
+}
+
+/// Reads an array from a NumPy `.npy` file.
+///
+/// The array gets NumPy's `shape` as dims, padded with ones, and the matching
+/// dtype: `f4`, `f8`, `c8`, `c16`, `b1`, `u1`, `i2`, `u2`, `i4`, `u4`, `i8`
+/// and `u8` are supported in either byte order. C-order data is transposed
+/// into arrayfire's column-major order, Fortran-order data is used as is.
+/// Shapes of more than 4 dimensions fail with `Error::Format`.
+///
+/// ```rust,no_run
+/// extern crate arrayfire_serde;
+///
+/// use std::fs::File;
+///
+/// # fn main() {
+/// let mut file = File::open("weights.npy").unwrap();
+/// let array = arrayfire_serde::from_npy(&mut file).unwrap();
+/// # }
+/// ```
+pub fn from_npy<R: Read>(r: &mut R) -> Result<Array, Error> {
+    let NpyHeader {
+        dtype,
+        big_endian,
+        fortran_order,
+        dim,
+    } = read_header(r)?;
+
+    let elements = element_count(&dim)?;
+    if elements == 0 {
+        return empty_array(dtype, dim);
+    }
+    // read through `take` so corrupt shapes fail on EOF instead of allocating up front
+    let len = elements.saturating_mul(dtype_size(dtype));
+    let mut bytes = Vec::new();
+    r.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(Error::Io(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            ".npy data is truncated",
+        )));
+    }
+    if big_endian != cfg!(target_endian = "big") {
+        swap_bytes(&mut bytes, component_size(dtype));
+    }
+
+    // C-order data is column-major data of the reversed shape
+    let stored_dim = if fortran_order {
+        dim
+    } else {
+        Dim4::new(&[dim[3], dim[2], dim[1], dim[0]])
+    };
+    let array = match dtype {
+        // NumPy stores one byte per bool rather than packed bits
+        DType::B8 => {
+            let values: Vec<bool> = bytes.iter().map(|&b| b != 0).collect();
+            new_array(&values, stored_dim)?
+        }
+        _ => build_array(dtype, stored_dim, &bytes)?,
+    };
+    if fortran_order {
+        Ok(array)
+    } else {
+        reverse_axes(&array)
+    }
+}
+
+/// Writes `array` to `w` as a NumPy `.npy` file of format version 1.0.
+///
+/// The shape is the dims of the array without trailing ones, so a
+/// `[2, 3, 1, 1]` array becomes a `(2, 3)` NumPy array with element `[i, j]`
+/// equal to element `(i, j)`. The data is written in arrayfire's column-major
+/// order with `fortran_order` set, so no transpose is needed on either side.
+/// Multi-byte dtypes are written little-endian. `from_npy` reads the file back.
+///
+/// ```rust,no_run
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// use arrayfire::{Array, Dim4};
+/// use std::fs::File;
+///
+/// # fn main() {
+/// let array = Array::new(&[1.0f32, 2.0, 3.0, 4.0], Dim4::new(&[2, 2, 1, 1]));
+/// let mut file = File::create("weights.npy").unwrap();
+/// arrayfire_serde::to_npy(&mut file, &array).unwrap();
+/// # }
+/// ```
+pub fn to_npy<W: Write>(w: &mut W, array: &Array) -> io::Result<()> {
+    let dtype = array_dtype(array).map_err(invalid_input)?;
+    let dims = array.dims();
+    let ndims = dims
+        .get()
+        .iter()
+        .rposition(|&d| d != 1)
+        .map_or(1, |i| i + 1);
+    let shape: Vec<String> = dims.get()[..ndims].iter().map(u64::to_string).collect();
+    let shape = if ndims == 1 {
+        format!("({},)", shape[0])
+    } else {
+        format!("({})", shape.join(", "))
+    };
+    let mut header = format!(
+        "{{'descr': '{}', 'fortran_order': True, 'shape': {}, }}",
+        descr(dtype),
+        shape
+    );
+    // NumPy pads the header with spaces so the data starts 64-byte aligned;
+    // at most 4 dims keep it far below the u16 length limit of version 1.0
+    let unpadded = MAGIC.len() + 4 + header.len() + 1;
+    header.extend((0..(64 - unpadded % 64) % 64).map(|_| ' '));
+    header.push('\n');
+
+    w.write_all(MAGIC)?;
+    w.write_all(&[1, 0])?;
+    w.write_all(&(header.len() as u16).to_le_bytes())?;
+    w.write_all(header.as_bytes())?;
+
+    if array.elements() == 0 {
+        return Ok(());
+    }
+    if dtype == DType::B8 {
+        // NumPy stores one byte per bool rather than packed bits
+        let values: Vec<bool> = get_data(array).map_err(invalid_input)?;
+        let bytes: Vec<u8> = values.into_iter().map(u8::from).collect();
+        return w.write_all(&bytes);
+    }
+    with_host_bytes(array, |bytes| {
+        let mut bytes = bytes.to_vec();
+        swap_little_endian(&mut bytes, dtype);
+        w.write_all(&bytes)
+    })
+    .map_err(invalid_input)?
+}
@@ -0,0 +1,44 @@
+//! Serialization of optional `arrayfire` types.
+//!
+//! Intended for use with the `serde` attribute `#[serde(with = "arrayfire_serde::option")]`
+//! on `Option` fields, for example an `Option<arrayfire::Array>`:
+//!
+//! ```rust
+//! #[macro_use]
+//! extern crate serde_derive;
+//! extern crate serde;
+//! extern crate arrayfire;
+//! extern crate arrayfire_serde;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct MyStruct {
+//!     #[serde(with = "arrayfire_serde::option")]
+//!     mask: Option<arrayfire::Array>,
+//! }
+//! # fn main() {}
+//! ```
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{De, Ser};
+
+/// Serializes `Some` value in the same form as `arrayfire_serde::serialize`, or `None`.
+pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    for<'a> Ser<'a, T>: Serialize,
+    S: Serializer,
+{
+    match *value {
+        Some(ref value) => serializer.serialize_some(&Ser::new(value)),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Deserializes an option written by `serialize`.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    De<T>: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<De<T>>::deserialize(deserializer).map(|value| value.map(De::into_inner))
+}
@@ -0,0 +1,176 @@
+//! `Array` representation with a selectable element order.
+
+use arrayfire::{reorder, Array, DType, Dim4};
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::{self, SerializeTuple};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use core::fmt;
+
+use super::{
+    array_dtype, backend_call, check_size, check_version, empty_array, ArrayData, ArrayDataSeed,
+    DataEncoding, De, Ser, FORMAT_VERSION,
+};
+
+/// Element order of serialized array data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Order {
+    /// First dimension varies fastest, as stored by arrayfire.
+    ColumnMajor,
+    /// Last dimension varies fastest, as used by NumPy and C.
+    RowMajor,
+}
+
+impl Serialize for Order {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u8(match *self {
+            Order::ColumnMajor => 0,
+            Order::RowMajor => 1,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Order {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match u8::deserialize(deserializer)? {
+            0 => Ok(Order::ColumnMajor),
+            1 => Ok(Order::RowMajor),
+            value => Err(de::Error::custom(format!(
+                "invalid element order {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Reverses the axes of `array`; applying it twice restores the original.
+pub(crate) fn reverse_axes(array: &Array) -> Result<Array, super::Error> {
+    backend_call(|| reorder(array, Dim4::new(&[3, 2, 1, 0])))
+}
+
+/// Serializes an `Array` with its data in the given element order.
+///
+/// The representation is a `(version, dtype, dims, order, data)` tuple. `dims`
+/// are always arrayfire's own dimensions; with `Order::RowMajor` the last
+/// dimension varies fastest in `data`, as in a C-order NumPy array of shape
+/// `dims` reversed. Use `deserialize_ordered` to read the array back.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate serde;
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// use arrayfire_serde::Order;
+///
+/// fn row_major<S: serde::Serializer>(
+///     array: &arrayfire::Array,
+///     serializer: S,
+/// ) -> Result<S::Ok, S::Error> {
+///     arrayfire_serde::serialize_ordered(Order::RowMajor, array, serializer)
+/// }
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct MyStruct {
+///     #[serde(serialize_with = "row_major")]
+///     #[serde(deserialize_with = "arrayfire_serde::deserialize_ordered")]
+///     tensor: arrayfire::Array,
+/// }
+/// # fn main() {}
+/// ```
+pub fn serialize_ordered<S>(order: Order, array: &Array, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    backend_call(|| array.eval()).map_err(ser::Error::custom)?;
+    let dim = array.dims();
+    let dtype = array_dtype(array).map_err(ser::Error::custom)?;
+    let empty = dim.elements() == 0;
+
+    let mut tup = serializer.serialize_tuple(if empty { 4 } else { 5 })?;
+    tup.serialize_element(&FORMAT_VERSION)?;
+    tup.serialize_element(&Ser::new(&dtype))?;
+    tup.serialize_element(&Ser::new(&dim))?;
+    tup.serialize_element(&order)?;
+    if !empty {
+        match order {
+            Order::ColumnMajor => tup.serialize_element(&ArrayData {
+                array,
+                encoding: DataEncoding::Plain,
+            })?,
+            Order::RowMajor => {
+                let reversed = reverse_axes(array).map_err(ser::Error::custom)?;
+                tup.serialize_element(&ArrayData {
+                    array: &reversed,
+                    encoding: DataEncoding::Plain,
+                })?
+            }
+        }
+    }
+    tup.end()
+}
+
+/// Deserializes an `Array` written by `serialize_ordered` in either order.
+pub fn deserialize_ordered<'de, D>(deserializer: D) -> Result<Array, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct OrderedVisitor;
+
+    impl<'de> Visitor<'de> for OrderedVisitor {
+        type Value = Array;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "tuple as a seq of 5 elements")
+        }
+
+        fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+        where
+            V: SeqAccess<'de>,
+        {
+            let version: u16 = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 5 elements, found fewer"))?;
+            check_version(version)?;
+            let dtype: De<DType> = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 5 elements, found fewer"))?;
+            let dim: De<Dim4> = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 5 elements, found fewer"))?;
+            let order: Order = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 5 elements, found fewer"))?;
+            let (dtype, dim) = (dtype.0, dim.0);
+            check_size(&dim, None).map_err(de::Error::custom)?;
+            if dim.elements() == 0 {
+                return empty_array(dtype, dim).map_err(de::Error::custom);
+            }
+
+            let stored_dim = match order {
+                Order::ColumnMajor => dim,
+                Order::RowMajor => Dim4::new(&[dim[3], dim[2], dim[1], dim[0]]),
+            };
+            let seed = ArrayDataSeed {
+                dtype,
+                dim: stored_dim,
+                encoding: DataEncoding::Plain,
+            };
+            let array = seq
+                .next_element_seed(seed)?
+                .ok_or_else(|| de::Error::custom("expected 5 elements, found fewer"))?;
+            match order {
+                Order::ColumnMajor => Ok(array),
+                Order::RowMajor => reverse_axes(&array).map_err(de::Error::custom),
+            }
+        }
+    }
+
+    deserializer.deserialize_tuple(5, OrderedVisitor)
+}
@@ -0,0 +1,407 @@
+//! Packed `Array` representation storing the element data as a single byte blob.
+
+use arrayfire::{Array, DType, Dim4, HasAfEnum};
+use num::Complex;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::{self, SerializeTuple};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::{fmt, mem, ptr, slice};
+
+use super::{
+    array_dtype, backend_call, check_version, element_count, empty_array, get_data, De, Error, Ser,
+    FORMAT_VERSION,
+};
+
+const LITTLE_ENDIAN: u8 = 0;
+const BIG_ENDIAN: u8 = 1;
+
+fn native_endianness() -> u8 {
+    if cfg!(target_endian = "big") {
+        BIG_ENDIAN
+    } else {
+        LITTLE_ENDIAN
+    }
+}
+
+/// Size in bytes of the packed data of `elements` values of `dtype`.
+///
+/// Saturates at `usize::MAX`, which no valid input can match.
+pub(crate) fn data_len(dtype: DType, elements: usize) -> usize {
+    match dtype {
+        DType::B8 => elements.div_ceil(8),
+        _ => elements.saturating_mul(dtype_size(dtype)),
+    }
+}
+
+/// Size in bytes of a single element of `dtype`.
+pub(crate) fn dtype_size(dtype: DType) -> usize {
+    match dtype {
+        DType::B8 | DType::U8 => 1,
+        DType::S16 | DType::U16 => 2,
+        DType::F32 | DType::S32 | DType::U32 => 4,
+        DType::F64 | DType::S64 | DType::U64 | DType::C32 => 8,
+        DType::C64 => 16,
+    }
+}
+
+/// Size in bytes of the scalar components of `dtype`, which is the unit of byte swapping.
+pub(crate) fn component_size(dtype: DType) -> usize {
+    match dtype {
+        DType::C32 | DType::C64 => dtype_size(dtype) / 2,
+        _ => dtype_size(dtype),
+    }
+}
+
+/// Reverses the byte order of every `width` byte chunk of `bytes`.
+pub(crate) fn swap_bytes(bytes: &mut [u8], width: usize) {
+    if width > 1 {
+        for chunk in bytes.chunks_mut(width) {
+            chunk.reverse();
+        }
+    }
+}
+
+/// Converts element data between host and little-endian byte order.
+#[cfg(any(feature = "std", feature = "base64"))]
+pub(crate) fn swap_little_endian(bytes: &mut [u8], dtype: DType) {
+    if cfg!(target_endian = "big") {
+        swap_bytes(bytes, component_size(dtype));
+    }
+}
+
+/// Serializes a byte slice with `serialize_bytes`.
+struct Bytes<'a>(&'a [u8]);
+
+impl<'a> Serialize for Bytes<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+/// Deserializes an owned byte buffer from bytes or a sequence of `u8`.
+pub(crate) struct ByteBuf(pub(crate) Vec<u8>);
+
+impl<'de> Deserialize<'de> for ByteBuf {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ByteBufVisitor;
+
+        impl<'de> Visitor<'de> for ByteBufVisitor {
+            type Value = ByteBuf;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "byte array")
+            }
+
+            fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(ByteBuf(value.to_vec()))
+            }
+
+            fn visit_byte_buf<E>(self, value: Vec<u8>) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(ByteBuf(value))
+            }
+
+            fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+            where
+                V: SeqAccess<'de>,
+            {
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+                while let Some(byte) = seq.next_element()? {
+                    bytes.push(byte);
+                }
+                Ok(ByteBuf(bytes))
+            }
+        }
+
+        deserializer.deserialize_byte_buf(ByteBufVisitor)
+    }
+}
+
+/// Deserializes bytes or a sequence of `u8`, borrowing from the input when the
+/// format hands out borrowed bytes and copying otherwise.
+pub(crate) struct CowBytes<'de>(pub(crate) Cow<'de, [u8]>);
+
+impl<'de> Deserialize<'de> for CowBytes<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct CowBytesVisitor;
+
+        impl<'de> Visitor<'de> for CowBytesVisitor {
+            type Value = CowBytes<'de>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                write!(formatter, "byte array")
+            }
+
+            fn visit_borrowed_bytes<E>(self, value: &'de [u8]) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(CowBytes(Cow::Borrowed(value)))
+            }
+
+            fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(CowBytes(Cow::Owned(value.to_vec())))
+            }
+
+            fn visit_byte_buf<E>(self, value: Vec<u8>) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(CowBytes(Cow::Owned(value)))
+            }
+
+            fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+            where
+                V: SeqAccess<'de>,
+            {
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+                while let Some(byte) = seq.next_element()? {
+                    bytes.push(byte);
+                }
+                Ok(CowBytes(Cow::Owned(bytes)))
+            }
+        }
+
+        deserializer.deserialize_bytes(CowBytesVisitor)
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Array element types without padding bytes, whose slices can be viewed as bytes.
+///
+/// Sealed, since `as_bytes` relies on every byte of a value being initialized.
+pub(crate) trait PlainData: sealed::Sealed + Copy {}
+
+macro_rules! plain_data {
+    ($($t:ty),*) => {
+        $(
+            impl sealed::Sealed for $t {}
+            impl PlainData for $t {}
+        )*
+    };
+}
+
+// `Complex` is `#[repr(C)]` with two fields of the same type, so it has no padding either
+plain_data!(
+    bool,
+    u8,
+    i16,
+    u16,
+    i32,
+    u32,
+    i64,
+    u64,
+    f32,
+    f64,
+    Complex<f32>,
+    Complex<f64>
+);
+
+pub(crate) fn as_bytes<T: PlainData>(data: &[T]) -> &[u8] {
+    // SAFETY: `PlainData` types have no padding, so all `size_of_val(data)` bytes are initialized
+    unsafe { slice::from_raw_parts(data.as_ptr() as *const u8, mem::size_of_val(data)) }
+}
+
+/// Evaluates the array, copies its data to the host and passes it to `f` as raw bytes in native byte order.
+pub(crate) fn with_host_bytes<F, R>(array: &Array, f: F) -> Result<R, Error>
+where
+    F: FnOnce(&[u8]) -> R,
+{
+    if array.elements() == 0 {
+        return Ok(f(&[]));
+    }
+    backend_call(|| array.eval())?;
+    let dtype = array_dtype(array)?;
+    Ok(match dtype {
+        DType::F32 => f(as_bytes(&get_data::<f32>(array)?)),
+        DType::F64 => f(as_bytes(&get_data::<f64>(array)?)),
+        DType::S16 => f(as_bytes(&get_data::<i16>(array)?)),
+        DType::S32 => f(as_bytes(&get_data::<i32>(array)?)),
+        DType::S64 => f(as_bytes(&get_data::<i64>(array)?)),
+        DType::U8 => f(&get_data::<u8>(array)?),
+        DType::U16 => f(as_bytes(&get_data::<u16>(array)?)),
+        DType::U32 => f(as_bytes(&get_data::<u32>(array)?)),
+        DType::U64 => f(as_bytes(&get_data::<u64>(array)?)),
+        DType::B8 => f(&pack_bits(&get_data::<bool>(array)?)),
+        DType::C32 => f(as_bytes(&get_data::<Complex<f32>>(array)?)),
+        DType::C64 => f(as_bytes(&get_data::<Complex<f64>>(array)?)),
+    })
+}
+
+/// Builds an array from raw bytes in native byte order.
+///
+/// `bytes` must hold exactly `dim.elements()` values of `T`.
+fn array_from_bytes<T: HasAfEnum + Copy + Default>(bytes: &[u8], dim: Dim4) -> Array {
+    let mut data: Vec<T> = vec![T::default(); bytes.len() / mem::size_of::<T>()];
+    unsafe {
+        ptr::copy_nonoverlapping(bytes.as_ptr(), data.as_mut_ptr() as *mut u8, bytes.len());
+    }
+    Array::new::<T>(data.as_slice(), dim)
+}
+
+/// Packs booleans eight to a byte, least significant bit first.
+pub(crate) fn pack_bits(values: &[bool]) -> Vec<u8> {
+    values
+        .chunks(8)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .fold(0u8, |byte, (i, &bit)| byte | (bit as u8) << i)
+        })
+        .collect()
+}
+
+/// Unpacks the first `len` booleans of bits packed by `pack_bits`.
+pub(crate) fn unpack_bits(bytes: &[u8], len: usize) -> Vec<bool> {
+    (0..len).map(|i| bytes[i / 8] >> (i % 8) & 1 == 1).collect()
+}
+
+pub(crate) fn build_array(dtype: DType, dim: Dim4, bytes: &[u8]) -> Result<Array, Error> {
+    let expected = element_count(&dim)?;
+    if bytes.len() != data_len(dtype, expected) {
+        let found = match dtype {
+            DType::B8 => bytes.len() * 8,
+            _ => bytes.len() / dtype_size(dtype),
+        };
+        return Err(Error::LengthMismatch { expected, found });
+    }
+    if expected == 0 {
+        return empty_array(dtype, dim);
+    }
+    backend_call(|| match dtype {
+        DType::F32 => array_from_bytes::<f32>(bytes, dim),
+        DType::F64 => array_from_bytes::<f64>(bytes, dim),
+        DType::S16 => array_from_bytes::<i16>(bytes, dim),
+        DType::S32 => array_from_bytes::<i32>(bytes, dim),
+        DType::S64 => array_from_bytes::<i64>(bytes, dim),
+        DType::U8 => Array::new(bytes, dim),
+        DType::U16 => array_from_bytes::<u16>(bytes, dim),
+        DType::U32 => array_from_bytes::<u32>(bytes, dim),
+        DType::U64 => array_from_bytes::<u64>(bytes, dim),
+        DType::B8 => Array::new(&unpack_bits(bytes, expected), dim),
+        DType::C32 => array_from_bytes::<Complex<f32>>(bytes, dim),
+        DType::C64 => array_from_bytes::<Complex<f64>>(bytes, dim),
+    })
+}
+
+/// Serializes an `Array` with its element data as a single `serialize_bytes` blob.
+///
+/// The representation is a `(version, dtype, dims, endianness, bytes)` tuple,
+/// where `bytes` holds the column-major host data in the byte order given by
+/// `endianness` (`0` little, `1` big). Compared to `serialize` this avoids
+/// per-element overhead in formats with a native byte string type.
+///
+/// `B8` arrays are bit packed, eight elements per byte with the least significant
+/// bit first; the trailing byte is zero padded.
+///
+/// `deserialize_packed` byte swaps the data when `endianness` differs from
+/// the host, so data written on a big-endian host loads on a little-endian one.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate serde;
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct MyStruct {
+///     #[serde(serialize_with = "arrayfire_serde::serialize_packed")]
+///     #[serde(deserialize_with = "arrayfire_serde::deserialize_packed")]
+///     tensor: arrayfire::Array,
+/// }
+/// # fn main() {}
+/// ```
+pub fn serialize_packed<S>(array: &Array, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let dim = array.dims();
+    let dtype = array_dtype(array).map_err(ser::Error::custom)?;
+
+    let mut tup = serializer.serialize_tuple(5)?;
+    tup.serialize_element(&FORMAT_VERSION)?;
+    tup.serialize_element(&Ser::new(&dtype))?;
+    tup.serialize_element(&Ser::new(&dim))?;
+    tup.serialize_element(&native_endianness())?;
+    with_host_bytes(array, |bytes| tup.serialize_element(&Bytes(bytes)))
+        .map_err(ser::Error::custom)??;
+    tup.end()
+}
+
+/// Deserializes an `Array` written by `serialize_packed`.
+pub fn deserialize_packed<'de, D>(deserializer: D) -> Result<Array, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct PackedVisitor;
+
+    impl<'de> Visitor<'de> for PackedVisitor {
+        type Value = Array;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "tuple as a seq of 5 elements")
+        }
+
+        fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+        where
+            V: SeqAccess<'de>,
+        {
+            let version: u16 = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 5 elements, found fewer"))?;
+            check_version(version)?;
+            let dtype: De<DType> = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 5 elements, found fewer"))?;
+            let dim: De<Dim4> = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 5 elements, found fewer"))?;
+            let endianness: u8 = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 5 elements, found fewer"))?;
+            let mut bytes: ByteBuf = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::custom("expected 5 elements, found fewer"))?;
+
+            match endianness {
+                LITTLE_ENDIAN | BIG_ENDIAN => {}
+                _ => {
+                    return Err(de::Error::custom(format_args!(
+                        "invalid endianness marker {}",
+                        endianness
+                    )))
+                }
+            }
+            if endianness != native_endianness() {
+                swap_bytes(&mut bytes.0, component_size(dtype.0));
+            }
+            build_array(dtype.0, dim.0, &bytes.0).map_err(de::Error::custom)
+        }
+    }
+
+    deserializer.deserialize_tuple(5, PackedVisitor)
+}
@@ -0,0 +1,105 @@
+//! `Array` representation with floats rounded for human-readable output.
+
+use arrayfire::{Array, HasAfEnum};
+use num::{Complex, Float};
+use serde::ser;
+use serde::{Serialize, Serializer};
+use alloc::vec::Vec;
+
+use super::{get_data, serialize_array, DataEncoding};
+
+/// Rounds `value` to `decimals` decimal places, leaving it unchanged where
+/// scaling it would overflow, e.g. for non-finite values.
+fn round<T: Float>(value: T, decimals: u32) -> T {
+    let exponent = if decimals > i32::MAX as u32 {
+        i32::MAX
+    } else {
+        decimals as i32
+    };
+    let factor = match T::from(10) {
+        Some(ten) => ten.powi(exponent),
+        None => return value,
+    };
+    let scaled = value * factor;
+    if scaled.is_finite() {
+        scaled.round() / factor
+    } else {
+        value
+    }
+}
+
+pub(crate) fn serialize_floats<T, S>(
+    array: &Array,
+    decimals: u32,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    T: Float + HasAfEnum + Default + Serialize,
+    S: Serializer,
+{
+    get_data::<T>(array)
+        .map_err(ser::Error::custom)?
+        .into_iter()
+        .map(|f| round(f, decimals))
+        .collect::<Vec<_>>()
+        .serialize(serializer)
+}
+
+pub(crate) fn serialize_complex<T, S>(
+    array: &Array,
+    decimals: u32,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    T: Float + Default + Serialize,
+    Complex<T>: HasAfEnum,
+    S: Serializer,
+{
+    get_data::<Complex<T>>(array)
+        .map_err(ser::Error::custom)?
+        .into_iter()
+        .map(|c| (round(c.re, decimals), round(c.im, decimals)))
+        .collect::<Vec<_>>()
+        .serialize(serializer)
+}
+
+/// Serializes an `Array` like `serialize`, except that in human-readable
+/// formats the elements of float and complex arrays are rounded to `decimals`
+/// decimal places.
+///
+/// Intended for JSON or YAML dumps meant for inspection, where full precision
+/// is noise. Compact formats such as bincode always get the exact values, so
+/// no data is lost where nobody reads the output by eye. Non-finite values and
+/// other dtypes are written unchanged. The output is read back by
+/// `deserialize`.
+///
+/// Takes the number of decimals as an extra first argument, so it is used
+/// through a wrapper function:
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate serde;
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// fn serialize_3<S: serde::Serializer>(
+///     array: &arrayfire::Array,
+///     serializer: S,
+/// ) -> Result<S::Ok, S::Error> {
+///     arrayfire_serde::serialize_rounded(3, array, serializer)
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Report {
+///     #[serde(serialize_with = "serialize_3")]
+///     losses: arrayfire::Array,
+/// }
+/// # fn main() {}
+/// ```
+pub fn serialize_rounded<S>(decimals: u32, array: &Array, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serialize_array(array, serializer, DataEncoding::Rounded(decimals))
+}
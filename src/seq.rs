@@ -0,0 +1,74 @@
+//! Serialization of slices of `arrayfire` types as a `serde` sequence.
+//!
+//! Intended for use with the `serde` attribute `#[serde(with = "arrayfire_serde::seq")]`
+//! on `Vec` fields, for example a `Vec<arrayfire::Array>`:
+//!
+//! ```rust
+//! #[macro_use]
+//! extern crate serde_derive;
+//! extern crate serde;
+//! extern crate arrayfire;
+//! extern crate arrayfire_serde;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct MyStruct {
+//!     #[serde(with = "arrayfire_serde::seq")]
+//!     batch: Vec<arrayfire::Array>,
+//! }
+//! # fn main() {}
+//! ```
+
+use serde::de::{SeqAccess, Visitor};
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+
+use super::{De, Ser};
+
+/// Serializes each element of `values` in the same form as `arrayfire_serde::serialize`.
+pub fn serialize<T, S>(values: &[T], serializer: S) -> Result<S::Ok, S::Error>
+where
+    for<'a> Ser<'a, T>: Serialize,
+    S: Serializer,
+{
+    let mut seq = serializer.serialize_seq(Some(values.len()))?;
+    for value in values {
+        seq.serialize_element(&Ser::new(value))?;
+    }
+    seq.end()
+}
+
+/// Deserializes a sequence written by `serialize`.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    De<T>: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    struct VecVisitor<T>(PhantomData<T>);
+
+    impl<'de, T> Visitor<'de> for VecVisitor<T>
+    where
+        De<T>: Deserialize<'de>,
+    {
+        type Value = Vec<T>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            write!(formatter, "a sequence")
+        }
+
+        fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+        where
+            V: SeqAccess<'de>,
+        {
+            let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+            while let Some(value) = seq.next_element::<De<T>>()? {
+                values.push(value.into_inner());
+            }
+            Ok(values)
+        }
+    }
+
+    deserializer.deserialize_seq(VecVisitor(PhantomData))
+}
@@ -0,0 +1,124 @@
+//! Sparse matrices in coordinate (COO) form, stored as plain arrays.
+
+use arrayfire::{Array, DType, Dim4};
+use serde::{de, ser};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{array_dtype, De, Error, Ser};
+
+/// A sparse array in coordinate form: `values[i]` is at row `rows[i]` and
+/// column `cols[i]` of an array of dims `shape`.
+///
+/// Serialized as a `CooSparse` struct with `rows`, `cols`, `values` and `shape`
+/// fields, each in the same form as `serialize`. Serializing and deserializing
+/// fail if `rows` or `cols` do not have an integer dtype, or if the three
+/// arrays differ in length.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate serde;
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// use arrayfire_serde::CooSparse;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Graph {
+///     adjacency: CooSparse,
+/// }
+/// # fn main() {}
+/// ```
+pub struct CooSparse {
+    pub rows: Array,
+    pub cols: Array,
+    pub values: Array,
+    pub shape: Dim4,
+}
+
+/// Private helper for serializing a `CooSparse`.
+#[derive(Serialize)]
+#[serde(rename = "CooSparse")]
+struct CooSparseRef<'a> {
+    rows: Ser<'a, Array>,
+    cols: Ser<'a, Array>,
+    values: Ser<'a, Array>,
+    shape: Ser<'a, Dim4>,
+}
+
+/// Private helper for deserializing a `CooSparse`.
+#[derive(Deserialize)]
+#[serde(rename = "CooSparse", deny_unknown_fields)]
+struct CooSparseRepr {
+    rows: De<Array>,
+    cols: De<Array>,
+    values: De<Array>,
+    shape: De<Dim4>,
+}
+
+/// Fails unless the coordinates in `array` have an integer dtype.
+fn check_indices(name: &str, array: &Array) -> Result<(), Error> {
+    match array_dtype(array)? {
+        DType::U8 | DType::S16 | DType::U16 | DType::S32 | DType::U32 | DType::S64 | DType::U64 => {
+            Ok(())
+        }
+        dtype => Err(Error::Format(format!(
+            "sparse {} must have an integer dtype, found {:?}",
+            name, dtype
+        ))),
+    }
+}
+
+impl CooSparse {
+    /// Checks the dtypes of the coordinates and that all three arrays have the
+    /// same number of elements.
+    fn check(&self) -> Result<(), Error> {
+        check_indices("rows", &self.rows)?;
+        check_indices("cols", &self.cols)?;
+        let lengths = (
+            self.rows.elements(),
+            self.cols.elements(),
+            self.values.elements(),
+        );
+        if lengths.0 != lengths.2 || lengths.1 != lengths.2 {
+            return Err(Error::Format(format!(
+                "sparse rows, cols and values differ in length: {}, {} and {}",
+                lengths.0, lengths.1, lengths.2
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for CooSparse {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.check().map_err(ser::Error::custom)?;
+        CooSparseRef {
+            rows: Ser::new(&self.rows),
+            cols: Ser::new(&self.cols),
+            values: Ser::new(&self.values),
+            shape: Ser::new(&self.shape),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CooSparse {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let repr = CooSparseRepr::deserialize(deserializer)?;
+        let sparse = CooSparse {
+            rows: repr.rows.0,
+            cols: repr.cols.0,
+            values: repr.values.0,
+            shape: repr.shape.0,
+        };
+        sparse.check().map_err(de::Error::custom)?;
+        Ok(sparse)
+    }
+}
@@ -0,0 +1,153 @@
+//! Streams of arrays in the binary array format, e.g. an append-only log file.
+//!
+//! Each record is a `u64` little-endian byte length followed by one array as
+//! written by `write_array`. The length lets `ArrayReader` skip records without
+//! decoding them and tell a clean end of the stream from a truncated record.
+
+use arrayfire::Array;
+use std::io::{self, Read, Write};
+
+use super::binary::{invalid_input, read_array, write_array, HEADER_LEN};
+use super::packed::data_len;
+use super::{array_dtype, Error};
+
+/// Appends length-prefixed arrays to a `Write`.
+///
+/// ```rust,no_run
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// use arrayfire::{Array, Dim4};
+/// use arrayfire_serde::{ArrayReader, ArrayWriter};
+/// use std::fs::{File, OpenOptions};
+///
+/// # fn main() {
+/// let log = OpenOptions::new().append(true).create(true).open("log.afsd").unwrap();
+/// let mut writer = ArrayWriter::new(log);
+/// writer.push(&Array::new(&[1.0f32, 2.0], Dim4::new(&[2, 1, 1, 1]))).unwrap();
+///
+/// for array in ArrayReader::new(File::open("log.afsd").unwrap()) {
+///     let array = array.unwrap();
+/// }
+/// # }
+/// ```
+pub struct ArrayWriter<W: Write> {
+    w: W,
+}
+
+impl<W: Write> ArrayWriter<W> {
+    pub fn new(w: W) -> Self {
+        ArrayWriter { w }
+    }
+
+    /// Writes `array` as the next record.
+    pub fn push(&mut self, array: &Array) -> io::Result<()> {
+        let dtype = array_dtype(array).map_err(invalid_input)?;
+        let len = (HEADER_LEN + data_len(dtype, array.elements())) as u64;
+        self.w.write_all(&len.to_le_bytes())?;
+        write_array(&mut self.w, array)
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.w.flush()
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.w
+    }
+}
+
+/// Reads the records written by `ArrayWriter` in order.
+///
+/// As an iterator it yields each array until the end of the stream, and stops
+/// after the first error, such as a truncated record.
+pub struct ArrayReader<R: Read> {
+    r: R,
+    done: bool,
+}
+
+impl<R: Read> ArrayReader<R> {
+    pub fn new(r: R) -> Self {
+        ArrayReader { r, done: false }
+    }
+
+    /// Reads the length prefix of the next record, or `None` at the end of the stream.
+    fn next_len(&mut self) -> Result<Option<u64>, Error> {
+        let mut buf = [0u8; 8];
+        let mut read = 0;
+        while read < buf.len() {
+            match self.r.read(&mut buf[read..]) {
+                Ok(0) if read == 0 => return Ok(None),
+                Ok(0) => {
+                    return Err(Error::Io(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "record length is truncated",
+                    )))
+                }
+                Ok(n) => read += n,
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(Error::Io(err)),
+            }
+        }
+        Ok(Some(u64::from_le_bytes(buf)))
+    }
+
+    /// Reads the next array, or `None` at the end of the stream.
+    pub fn read_next(&mut self) -> Result<Option<Array>, Error> {
+        let len = match self.next_len()? {
+            Some(len) => len,
+            None => return Ok(None),
+        };
+        let mut record = (&mut self.r).take(len);
+        let array = read_array(&mut record)?;
+        // tolerate trailing bytes within a record, so records can grow new fields
+        io::copy(&mut record, &mut io::sink())?;
+        Ok(Some(array))
+    }
+
+    /// Skips the next record without decoding it.
+    ///
+    /// Returns `false` at the end of the stream.
+    pub fn skip_record(&mut self) -> Result<bool, Error> {
+        let len = match self.next_len()? {
+            Some(len) => len,
+            None => return Ok(false),
+        };
+        let skipped = io::copy(&mut (&mut self.r).take(len), &mut io::sink())?;
+        if skipped != len {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "record is truncated",
+            )));
+        }
+        Ok(true)
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.r
+    }
+}
+
+impl<R: Read> Iterator for ArrayReader<R> {
+    type Item = Result<Array, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.read_next() {
+            Ok(Some(array)) => Some(Ok(array)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Assertions for the tests of downstream crates.
+
+use arrayfire::Array;
+use alloc::string::String;
+
+use super::{array_dtype, arrays_equal, from_bytes, to_bytes};
+
+/// Describes `array` by its dtype and dims for assertion messages.
+fn describe(array: &Array) -> String {
+    match array_dtype(array) {
+        Ok(dtype) => format!("{:?} array of dims {:?}", dtype, array.dims().get()),
+        Err(_) => format!("array of dims {:?}", array.dims().get()),
+    }
+}
+
+/// Asserts that `array` survives a bincode round trip through `to_bytes` and
+/// `from_bytes`, comparing the result with `arrays_equal`.
+///
+/// Panics with a message naming the dtype and dims of both arrays if the
+/// round trip fails or changes the array. Intended for the tests of crates
+/// that store arrays with this crate, e.g. to check arrays produced by their
+/// own code.
+///
+/// Requires the `test-util` feature.
+///
+/// ```rust
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// use arrayfire::{Array, Dim4};
+///
+/// # fn main() {
+/// let array = Array::new(&[1.0f32, 2.0], Dim4::new(&[2, 1, 1, 1]));
+/// arrayfire_serde::assert_roundtrips(&array);
+/// # }
+/// ```
+#[allow(clippy::panic)]
+pub fn assert_roundtrips(array: &Array) {
+    let bytes = match to_bytes(array) {
+        Ok(bytes) => bytes,
+        Err(err) => panic!("serializing {} failed: {}", describe(array), err),
+    };
+    let de_array = match from_bytes(&bytes) {
+        Ok(de_array) => de_array,
+        Err(err) => panic!(
+            "deserializing {} from {} bytes failed: {}",
+            describe(array),
+            bytes.len(),
+            err
+        ),
+    };
+    if !arrays_equal(array, &de_array) {
+        panic!(
+            "{} did not round trip, read back {}",
+            describe(array),
+            describe(&de_array)
+        );
+    }
+}
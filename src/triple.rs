@@ -0,0 +1,42 @@
+//! Serialization of `(Array, Dim4, DType)` tuples.
+//!
+//! Intended for use with the `serde` attribute `#[serde(with = "arrayfire_serde::triple")]`
+//! on a tuple field:
+//!
+//! ```rust
+//! #[macro_use]
+//! extern crate serde_derive;
+//! extern crate serde;
+//! extern crate arrayfire;
+//! extern crate arrayfire_serde;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Config {
+//!     #[serde(with = "arrayfire_serde::triple")]
+//!     weights: (arrayfire::Array, arrayfire::Dim4, arrayfire::DType),
+//! }
+//! # fn main() {}
+//! ```
+
+use arrayfire::{Array, DType, Dim4};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{De, Ser};
+
+/// Serializes the tuple as a 3-tuple of its elements, each in the same form
+/// as `arrayfire_serde::serialize`.
+pub fn serialize<S>(value: &(Array, Dim4, DType), serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    (Ser::new(&value.0), Ser::new(&value.1), Ser::new(&value.2)).serialize(serializer)
+}
+
+/// Deserializes a tuple written by `serialize`.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<(Array, Dim4, DType), D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let (array, dim, dtype) = <(De<Array>, De<Dim4>, De<DType>)>::deserialize(deserializer)?;
+    Ok((array.into_inner(), dim.into_inner(), dtype.into_inner()))
+}
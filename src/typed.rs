@@ -0,0 +1,74 @@
+//! `Array` wrapper with a compile-time element type.
+
+use arrayfire::{Array, HasAfEnum};
+use serde::ser;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use core::marker::PhantomData;
+
+use super::{array_dtype, check_dtype, ArrayVisitor, DataEncoding, Ser, ARRAY_FIELDS};
+
+/// An `Array` whose elements are known to be of type `T`.
+///
+/// It uses the same representation as `serialize`, so data written from an
+/// untyped `Array` can be read back as a `TypedArray` and vice versa.
+/// Serializing fails if the wrapped array is not of type `T`, and
+/// deserializing fails with `Error::DTypeMismatch` before reading the data
+/// if the stored dtype is not that of `T`.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate serde;
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// use arrayfire_serde::TypedArray;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct MyStruct {
+///     weights: TypedArray<f32>,
+/// }
+/// # fn main() {}
+/// ```
+pub struct TypedArray<T: HasAfEnum>(pub Array, PhantomData<T>);
+
+impl<T: HasAfEnum> TypedArray<T> {
+    /// Wraps `array`, which must hold elements of type `T` to be serialized.
+    pub fn new(array: Array) -> Self {
+        TypedArray(array, PhantomData)
+    }
+
+    /// Returns the wrapped `Array`.
+    pub fn into_inner(self) -> Array {
+        self.0
+    }
+}
+
+impl<T: HasAfEnum> Serialize for TypedArray<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let dtype = array_dtype(&self.0).map_err(ser::Error::custom)?;
+        check_dtype(dtype, Some(T::get_af_dtype())).map_err(ser::Error::custom)?;
+        Ser::new(&self.0).serialize(serializer)
+    }
+}
+
+impl<'de, T: HasAfEnum> Deserialize<'de> for TypedArray<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let visitor = ArrayVisitor {
+            encoding: DataEncoding::Plain,
+            max_elements: None,
+            dtype: Some(T::get_af_dtype()),
+            strict: false,
+            probe_empty: false,
+        };
+        deserializer
+            .deserialize_struct("Array", ARRAY_FIELDS, visitor)
+            .map(TypedArray::new)
+    }
+}
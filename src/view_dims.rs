@@ -0,0 +1,96 @@
+//! Dimensions of a view together with those of its parent array.
+
+use arrayfire::Dim4;
+use serde::de;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{De, Ser};
+
+/// The dims of a view into a parent array and the offset of the view in each
+/// dimension, from which the strides of the view can be recomputed.
+///
+/// Serialized as a `ViewDims` struct with `parent`, `view` and `offset` fields,
+/// each in the same form as a serialized `Dim4`. Deserializing fails if the
+/// view does not fit within the parent at its offset.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate serde_derive;
+/// extern crate serde;
+/// extern crate arrayfire;
+/// extern crate arrayfire_serde;
+///
+/// use arrayfire_serde::ViewDims;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Crop {
+///     region: ViewDims,
+/// }
+/// # fn main() {}
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewDims {
+    pub parent: Dim4,
+    pub view: Dim4,
+    pub offset: Dim4,
+}
+
+/// Private helper for serializing a `ViewDims`.
+#[derive(Serialize)]
+#[serde(rename = "ViewDims")]
+struct ViewDimsRef<'a> {
+    parent: Ser<'a, Dim4>,
+    view: Ser<'a, Dim4>,
+    offset: Ser<'a, Dim4>,
+}
+
+/// Private helper for deserializing a `ViewDims`.
+#[derive(Deserialize)]
+#[serde(rename = "ViewDims", deny_unknown_fields)]
+struct ViewDimsRepr {
+    parent: De<Dim4>,
+    view: De<Dim4>,
+    offset: De<Dim4>,
+}
+
+impl Serialize for ViewDims {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        ViewDimsRef {
+            parent: Ser::new(&self.parent),
+            view: Ser::new(&self.view),
+            offset: Ser::new(&self.offset),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ViewDims {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let repr = ViewDimsRepr::deserialize(deserializer)?;
+        let (parent, view, offset) = (repr.parent.0, repr.view.0, repr.offset.0);
+        for i in 0..4 {
+            match offset[i].checked_add(view[i]) {
+                Some(end) if end <= parent[i] => {}
+                _ => {
+                    return Err(de::Error::custom(format_args!(
+                        "view of {:?} at {:?} exceeds parent dims {:?}",
+                        view.get(),
+                        offset.get(),
+                        parent.get()
+                    )))
+                }
+            }
+        }
+        Ok(ViewDims {
+            parent,
+            view,
+            offset,
+        })
+    }
+}
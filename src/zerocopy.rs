@@ -0,0 +1,102 @@
+//! Array data as a single byte string in compact formats, for the `zerocopy`
+//! feature. Element slices are reinterpreted with `bytemuck`.
+
+use arrayfire::{Array, DType, Dim4, HasAfEnum};
+use bytemuck::{self, Pod};
+use num::Complex;
+use serde::ser;
+use serde::Serializer;
+use alloc::vec::Vec;
+
+use super::packed::{self, data_len, pack_bits};
+use super::{element_count, get_data, new_array, Error};
+
+/// Serializes the data of `array` with a single `serialize_bytes` call, in host byte order.
+pub(crate) fn serialize_data<S>(
+    array: &Array,
+    dtype: DType,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    fn values<T, S>(array: &Array, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: HasAfEnum + Pod + Default,
+        S: Serializer,
+    {
+        let data = get_data::<T>(array).map_err(ser::Error::custom)?;
+        serializer.serialize_bytes(bytemuck::cast_slice(&data))
+    }
+
+    fn complex<T, S>(array: &Array, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Pod + Default,
+        Complex<T>: HasAfEnum,
+        S: Serializer,
+    {
+        let data = get_data::<Complex<T>>(array).map_err(ser::Error::custom)?;
+        let parts: Vec<T> = data.iter().flat_map(|c| [c.re, c.im]).collect();
+        serializer.serialize_bytes(bytemuck::cast_slice(&parts))
+    }
+
+    match dtype {
+        DType::F32 => values::<f32, S>(array, serializer),
+        DType::F64 => values::<f64, S>(array, serializer),
+        DType::S16 => values::<i16, S>(array, serializer),
+        DType::S32 => values::<i32, S>(array, serializer),
+        DType::S64 => values::<i64, S>(array, serializer),
+        DType::U8 => values::<u8, S>(array, serializer),
+        DType::U16 => values::<u16, S>(array, serializer),
+        DType::U32 => values::<u32, S>(array, serializer),
+        DType::U64 => values::<u64, S>(array, serializer),
+        DType::B8 => {
+            let data = get_data::<bool>(array).map_err(ser::Error::custom)?;
+            serializer.serialize_bytes(&pack_bits(&data))
+        }
+        DType::C32 => complex::<f32, S>(array, serializer),
+        DType::C64 => complex::<f64, S>(array, serializer),
+    }
+}
+
+/// Builds an array from data written by `serialize_data`.
+pub(crate) fn build_array(dtype: DType, dim: Dim4, bytes: &[u8]) -> Result<Array, Error> {
+    fn values<T: HasAfEnum + Pod>(bytes: &[u8]) -> Vec<T> {
+        let mut values = vec![T::zeroed(); bytes.len() / core::mem::size_of::<T>()];
+        bytemuck::cast_slice_mut(&mut values).copy_from_slice(bytes);
+        values
+    }
+
+    fn complex<T: Pod>(bytes: &[u8], dim: Dim4) -> Result<Array, Error>
+    where
+        Complex<T>: HasAfEnum,
+    {
+        let mut parts = vec![T::zeroed(); bytes.len() / core::mem::size_of::<T>()];
+        bytemuck::cast_slice_mut(&mut parts).copy_from_slice(bytes);
+        let data: Vec<Complex<T>> = parts
+            .chunks(2)
+            .map(|c| Complex { re: c[0], im: c[1] })
+            .collect();
+        new_array(&data, dim)
+    }
+
+    let elements = element_count(&dim)?;
+    // length errors and empty arrays are left to the packed reader
+    if elements == 0 || bytes.len() != data_len(dtype, elements) {
+        return packed::build_array(dtype, dim, bytes);
+    }
+    match dtype {
+        DType::F32 => new_array(&values::<f32>(bytes), dim),
+        DType::F64 => new_array(&values::<f64>(bytes), dim),
+        DType::S16 => new_array(&values::<i16>(bytes), dim),
+        DType::S32 => new_array(&values::<i32>(bytes), dim),
+        DType::S64 => new_array(&values::<i64>(bytes), dim),
+        DType::U16 => new_array(&values::<u16>(bytes), dim),
+        DType::U32 => new_array(&values::<u32>(bytes), dim),
+        DType::U64 => new_array(&values::<u64>(bytes), dim),
+        DType::C32 => complex::<f32>(bytes, dim),
+        DType::C64 => complex::<f64>(bytes, dim),
+        // bytes already, or bit packed
+        DType::U8 | DType::B8 => packed::build_array(dtype, dim, bytes),
+    }
+}
@@ -0,0 +1,62 @@
+//! Uses the crate from a `#![no_std]` crate with `alloc`.
+//!
+//! Run with `cargo test --no-default-features --test no_std` to also check
+//! that the crate itself builds without `std`.
+#![no_std]
+
+extern crate alloc;
+extern crate arrayfire;
+extern crate arrayfire_serde;
+extern crate serde_test;
+
+use alloc::vec;
+use arrayfire::{Array, DType, Dim4};
+use arrayfire_serde::{deserialize, Ser};
+use serde_test::{assert_ser_tokens, Configure, Deserializer, Token};
+
+#[test]
+fn test_no_std_dim4() {
+    let dim = Dim4::new(&[2, 3, 1, 1]);
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(3),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&Ser::new(&dim), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    assert_eq!(deserialize::<Dim4, _>(&mut de).unwrap(), dim);
+}
+
+#[test]
+fn test_no_std_array() {
+    let dim = Dim4::new(&[2, 1, 1, 1]);
+    let array = Array::new::<i32>(&[3, -4], dim);
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("s32"),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(2) },
+        Token::I32(3),
+        Token::I32(-4),
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&Ser::new(&array).readable(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let de_array = deserialize::<Array, _>((&mut de).readable()).unwrap();
+    assert_eq!(de_array.get_type(), DType::S32);
+    let mut data = vec![0i32; de_array.elements()];
+    de_array.host(data.as_mut_slice());
+    assert_eq!(data, [3, -4]);
+}
@@ -1,11 +1,52 @@
 extern crate arrayfire;
 extern crate arrayfire_serde;
+#[cfg(feature = "arrow")]
+extern crate arrow_buffer;
+#[cfg(feature = "arrow")]
+extern crate arrow_schema;
+#[cfg(feature = "bincode")]
+extern crate bincode;
+#[cfg(feature = "logging")]
+extern crate log;
+extern crate num;
 extern crate serde;
+#[cfg(feature = "base64")]
+extern crate serde_json;
 extern crate serde_test;
+#[cfg(feature = "serde_with")]
+extern crate serde_with;
+extern crate tempfile;
 
-use arrayfire::{Array, DType, Dim4};
-use serde_test::{assert_ser_tokens, Deserializer, Token};
-use arrayfire_serde::{deserialize, Ser};
+use arrayfire::{
+    fft, get_active_backend, index, random_uniform, Array, Backend, DType, Dim4, HasAfEnum,
+    RandomEngine, RandomEngineType, Seq, transpose,
+};
+use num::Complex;
+use serde_test::{
+    assert_de_tokens, assert_ser_tokens, assert_ser_tokens_error, assert_tokens, Deserializer,
+    Token,
+};
+use arrayfire_serde::{
+    boxed, dim4_flexible, dim4_named, dim4_vec, dtype_vec, named_arrays, option, triple,
+};
+use arrayfire_serde::{
+    arrays_approx_equal, arrays_equal, deserialize, deserialize_as, deserialize_header,
+    deserialize_in_place, deserialize_json_safe, deserialize_legacy, deserialize_lenient,
+    deserialize_meta, deserialize_limited, deserialize_ordered, deserialize_flat, deserialize_full,
+    deserialize_image, serialize_complex, deserialize_complex, serialize_js_safe, serialize_meta,
+    deserialize_js_safe, deserialize_packed, deserialize_promote, deserialize_seq,
+    deserialize_strict, deserialize_with_layout, deserialize_window, serialize, serialize_full,
+    serialize_human, serialize_image, serialize_checked_finite, serialize_json_safe,
+    serialize_ordered, serialize_packed, serialize_rounded, serialize_seq, serialize_with_layout,
+    from_npy, to_npy, from_slice, migrate_dtype_tag, read_array, read_array_header, write_array,
+    write_array_with_checksum, ArrayMeta, ArrayReader, ChunkedArray, ComplexLayout, CooSparse,
+    ArrayWriter, Error, Layout, Order, Ser, Serde, TypedArray, ViewDims,
+};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_test::Configure;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::marker::PhantomData;
 
 #[test]
 fn test_dim4() {
@@ -26,26 +67,411 @@ fn test_dim4() {
     assert_eq!(de.next_token_opt(), None);
 }
 
+/// Serializes anything convertible into a `Ser`, as generic code would.
+fn assert_into_ser_tokens<'a, T, V>(value: V, tokens: &[Token])
+where
+    T: 'a,
+    V: Into<Ser<'a, T>>,
+    Ser<'a, T>: Serialize,
+{
+    assert_ser_tokens(&value.into().readable(), tokens);
+}
+
+#[test]
+fn test_ser_from() {
+    let dim = Dim4::new(&[1, 2, 3, 4]);
+    assert_into_ser_tokens(
+        &dim,
+        &[
+            Token::Tuple { len: 4 },
+            Token::U64(1),
+            Token::U64(2),
+            Token::U64(3),
+            Token::U64(4),
+            Token::TupleEnd,
+        ],
+    );
+    assert_into_ser_tokens(&DType::U8, &[Token::Str("u8")]);
+
+    let array = Array::new::<i32>(&[3], Dim4::new(&[1, 1, 1, 1]));
+    assert_into_ser_tokens(
+        &array,
+        &[
+            Token::Tuple { len: 4 },
+            Token::U16(1),
+            Token::Str("s32"),
+            Token::Tuple { len: 4 },
+            Token::U64(1),
+            Token::U64(1),
+            Token::U64(1),
+            Token::U64(1),
+            Token::TupleEnd,
+            Token::Seq { len: Some(1) },
+            Token::I32(3),
+            Token::SeqEnd,
+            Token::TupleEnd,
+        ],
+    );
+}
+
+#[test]
+fn test_dim4_large() {
+    // extents beyond `u32::MAX` are carried as `u64` without narrowing
+    let extents = [1 << 40, u64::from(u32::MAX) + 1, 1, u64::MAX];
+    let dim = Dim4::new(&extents);
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U64(1 << 40),
+        Token::U64(1 << 32),
+        Token::U64(1),
+        Token::U64(u64::MAX),
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&Ser::new(&dim), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let deserialized = deserialize::<Dim4, _>(&mut de).unwrap();
+    assert_eq!(deserialized.get(), &extents);
+    assert_eq!(de.next_token_opt(), None);
+}
+
+#[test]
+fn test_dim4_truncated() {
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U64(1),
+        Token::U64(2),
+        Token::TupleEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    assert!(deserialize::<Dim4, _>(&mut de).is_err());
+}
+
+#[test]
+fn test_dim4_trailing() {
+    let tokens = [
+        Token::Tuple { len: 5 },
+        Token::U64(1),
+        Token::U64(2),
+        Token::U64(3),
+        Token::U64(4),
+        Token::U64(5),
+        Token::TupleEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    let err = deserialize::<Dim4, _>(&mut de).err().unwrap();
+    assert_eq!(err.to_string(), "expected 4 elements, found more");
+}
+
 #[test]
 fn test_dtype() {
     let dtype = DType::F64;
     let tokens = [Token::U8(2)];
-    assert_ser_tokens(&Ser::new(&dtype), &tokens);
+    assert_ser_tokens(&Ser::new(&dtype).compact(), &tokens);
 
     let mut de = Deserializer::new(&tokens);
-    let deserialized = deserialize::<DType, _>(&mut de).unwrap();
+    let deserialized = deserialize::<DType, _>((&mut de).compact()).unwrap();
     assert_eq!(&deserialized, &dtype);
     assert_eq!(de.next_token_opt(), None);
 }
 
+/// Checks that `T` is serialized with the fixed dtype `tag`, both for the
+/// bindings' enum and for the dtype the library reports for an array.
+fn assert_dtype_tag<T: HasAfEnum + Clone + Default>(tag: u8) {
+    let dtype = T::get_af_dtype();
+    let tokens = [Token::U8(tag)];
+    assert_ser_tokens(&Ser::new(&dtype).compact(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let deserialized = deserialize::<DType, _>((&mut de).compact()).unwrap();
+    assert_eq!(deserialized, dtype);
+    assert_eq!(de.next_token_opt(), None);
+
+    // the binary header stores the dtype the library reports for the array
+    let array = Array::new::<T>(&[T::default()], Dim4::new(&[1, 1, 1, 1]));
+    let mut bytes = Vec::new();
+    write_array(&mut bytes, &array).unwrap();
+    assert_eq!(bytes[7], tag, "{:?}", dtype);
+}
+
+#[test]
+fn test_dtype_all() {
+    assert_dtype_tag::<f32>(0);
+    assert_dtype_tag::<Complex<f32>>(1);
+    assert_dtype_tag::<f64>(2);
+    assert_dtype_tag::<Complex<f64>>(3);
+    assert_dtype_tag::<bool>(4);
+    assert_dtype_tag::<i32>(5);
+    assert_dtype_tag::<u32>(6);
+    assert_dtype_tag::<u8>(7);
+    assert_dtype_tag::<i64>(8);
+    assert_dtype_tag::<u64>(9);
+    assert_dtype_tag::<i16>(10);
+    assert_dtype_tag::<u16>(11);
+}
+
+#[test]
+fn test_dtype_invalid() {
+    let tokens = [Token::U8(200)];
+    let mut de = Deserializer::new(&tokens);
+    assert!(deserialize::<DType, _>((&mut de).compact()).is_err());
+}
+
+#[test]
+fn test_dtype_tag_integer_types() {
+    let load = |token: Token| {
+        let tokens = [token];
+        let mut de = Deserializer::new(&tokens);
+        deserialize::<DType, _>((&mut de).compact()).map_err(|e| e.to_string())
+    };
+    // the C64 tag as every integer type a format might produce
+    for &token in &[
+        Token::U8(3),
+        Token::U16(3),
+        Token::U32(3),
+        Token::U64(3),
+        Token::I8(3),
+        Token::I16(3),
+        Token::I32(3),
+        Token::I64(3),
+    ] {
+        assert_eq!(load(token), Ok(DType::C64));
+    }
+
+    assert_eq!(
+        load(Token::I32(-1)).err().unwrap(),
+        "invalid value: integer `-1`, expected u8"
+    );
+    assert_eq!(
+        load(Token::U32(300)).err().unwrap(),
+        Error::InvalidDType(300).to_string()
+    );
+}
+
+#[test]
+fn test_dtype_name() {
+    for &(dtype, name) in &[(DType::F32, "f32"), (DType::U8, "u8"), (DType::C64, "c64")] {
+        assert_ser_tokens(&Ser::new(&dtype).readable(), &[Token::Str(name)]);
+        let tokens = [Token::Str(name)];
+        let mut de = Deserializer::new(&tokens);
+        assert_eq!(
+            deserialize::<DType, _>((&mut de).readable()).unwrap(),
+            dtype
+        );
+    }
+
+    // tags written before names were introduced are still accepted
+    let tokens = [Token::U8(2)];
+    let mut de = Deserializer::new(&tokens);
+    assert_eq!(
+        deserialize::<DType, _>((&mut de).readable()).unwrap(),
+        DType::F64
+    );
+
+    let tokens = [Token::Str("f16")];
+    let mut de = Deserializer::new(&tokens);
+    assert_eq!(
+        deserialize::<DType, _>((&mut de).readable())
+            .err()
+            .unwrap()
+            .to_string(),
+        "unknown variant `f16`, expected one of `f32`, `c32`, `f64`, `c64`, `b8`, `s32`, \
+         `u32`, `u8`, `s64`, `u64`, `s16`, `u16`"
+    );
+
+    let array = Array::new(&[1.5f64], Dim4::new(&[1, 1, 1, 1]));
+    let json = serde_json::to_string(&Ser::new(&array)).unwrap();
+    assert_eq!(json, r#"[1,"f64",[1,1,1,1],[1.5]]"#);
+    let de_array = serde_json::from_str::<Serde<Array>>(r#"[1,2,[1,1,1,1],[1.5]]"#).unwrap();
+    assert!(arrays_equal(&de_array.0, &array));
+}
+
+#[test]
+fn test_array_f16() {
+    extern "C" {
+        fn af_create_handle(out: *mut i64, ndims: u32, dims: *const i64, dtype: i32) -> i32;
+    }
+
+    // `f16` is af_dtype 12 in arrayfire 3.6 and later, with no `DType` variant in 3.5
+    let dims = [2i64, 1, 1, 1];
+    let mut handle = 0i64;
+    if unsafe { af_create_handle(&mut handle, 4, dims.as_ptr(), 12) } != 0 {
+        // the backend library predates `f16`
+        return;
+    }
+    let array = Array::from(handle);
+    let err = serde_json::to_string(&Ser::new(&array)).err().unwrap();
+    assert_eq!(err.to_string(), Error::InvalidDType(12).to_string());
+    let err = write_array(&mut Vec::new(), &array).err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert_eq!(err.to_string(), Error::InvalidDType(12).to_string());
+}
+
+#[test]
+fn test_migrate_dtype_tag() {
+    for old in 0..12 {
+        assert_eq!(migrate_dtype_tag(old).unwrap(), old);
+    }
+    match migrate_dtype_tag(12) {
+        Err(Error::InvalidDType(12)) => {}
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_array_legacy() {
+    // dtype 2 is F64 as an af_dtype discriminant, in the unversioned tuple
+    let tokens = [
+        Token::Tuple { len: 3 },
+        Token::U8(2),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(2) },
+        Token::F64(0.5),
+        Token::F64(-1.5),
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    for &readable in &[true, false] {
+        let mut de = Deserializer::new(&tokens);
+        let array = if readable {
+            deserialize_legacy((&mut de).readable())
+        } else {
+            deserialize_legacy((&mut de).compact())
+        };
+        assert_eq!(de.next_token_opt(), None);
+        assert!(arrays_equal(
+            &array.unwrap(),
+            &Array::new::<f64>(&[0.5, -1.5], Dim4::new(&[2, 1, 1, 1]))
+        ));
+    }
+
+    // empty arrays still carry their data sequence
+    let tokens = [
+        Token::Tuple { len: 3 },
+        Token::U8(0),
+        Token::Tuple { len: 4 },
+        Token::U64(0),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(0) },
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    let array = deserialize_legacy((&mut de).compact()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(array.elements(), 0);
+
+    // dtype 10 is S16, in the struct form
+    let tokens = [
+        Token::Struct {
+            name: "Array",
+            len: 4,
+        },
+        Token::Str("version"),
+        Token::U16(1),
+        Token::Str("dtype"),
+        Token::U8(10),
+        Token::Str("dims"),
+        Token::Tuple { len: 4 },
+        Token::U64(1),
+        Token::U64(3),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Str("data"),
+        Token::Seq { len: Some(3) },
+        Token::I16(-1),
+        Token::I16(0),
+        Token::I16(1),
+        Token::SeqEnd,
+        Token::StructEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    let array = deserialize_legacy((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert!(arrays_equal(
+        &array,
+        &Array::new::<i16>(&[-1, 0, 1], Dim4::new(&[1, 3, 1, 1]))
+    ));
+}
+
+#[cfg(feature = "bincode")]
+#[derive(Deserialize)]
+struct Legacy(#[serde(deserialize_with = "deserialize_legacy")] Array);
+
+#[cfg(feature = "bincode")]
+#[test]
+fn test_array_legacy_bincode() {
+    // written as the `(dtype, dims, data)` tuple of releases before the format version
+    let data: Vec<f32> = vec![1.0, 2.0, 3.0];
+    let bytes = bincode::serialize(&(0u8, (3u64, 1u64, 1u64, 1u64), &data)).unwrap();
+    let array = bincode::deserialize::<Legacy>(&bytes).unwrap().0;
+    assert!(arrays_equal(
+        &array,
+        &Array::new::<f32>(&data, Dim4::new(&[3, 1, 1, 1]))
+    ));
+
+    let bytes = bincode::serialize(&(9u8, (0u64, 1u64, 1u64, 1u64), Vec::<u64>::new())).unwrap();
+    let array = bincode::deserialize::<Legacy>(&bytes).unwrap().0;
+    assert_eq!(array.elements(), 0);
+}
+
+#[test]
+fn test_seq() {
+    let seq = Seq::new(0.0f32, 9.0, 1.0);
+    let tokens = [
+        Token::Tuple { len: 3 },
+        Token::F32(0.0),
+        Token::F32(9.0),
+        Token::F32(1.0),
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&Ser::new(&seq), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let deserialized = deserialize::<Seq<f32>, _>(&mut de).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(deserialized.begin(), seq.begin());
+    assert_eq!(deserialized.end(), seq.end());
+    assert_eq!(deserialized.step(), seq.step());
+}
+
+#[test]
+fn test_seq_span() {
+    let span: Seq<f32> = Seq::default();
+    let tokens = [
+        Token::Tuple { len: 3 },
+        Token::F32(1.0),
+        Token::F32(1.0),
+        Token::F32(0.0),
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&Ser::new(&span), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let deserialized = deserialize::<Seq<f32>, _>(&mut de).unwrap();
+    assert_eq!(deserialized.begin(), span.begin());
+    assert_eq!(deserialized.end(), span.end());
+    assert_eq!(deserialized.step(), span.step());
+}
+
 #[test]
 fn test_array() {
     let dim = Dim4::new(&[2, 2, 1, 1]);
     let values: [f64; 4] = [1.0, 2.0, 3.0, 4.0];
     let array = Array::new::<f64>(&values, dim);
     let tokens = [
-        Token::Tuple { len: 3 },
-        Token::U8(2),
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("f64"),
         Token::Tuple { len: 4 },
         Token::U64(2),
         Token::U64(2),
@@ -60,17 +486,3813 @@ fn test_array() {
         Token::SeqEnd,
         Token::TupleEnd,
     ];
-    assert_ser_tokens(&Ser::new(&array), &tokens);
+    assert_ser_tokens(&Ser::new(&array).readable(), &tokens);
 
     let mut de = Deserializer::new(&tokens);
-    let de_array = deserialize::<Array, _>(&mut de).unwrap();
+    let de_array = deserialize::<Array, _>((&mut de).readable()).unwrap();
     assert_eq!(de.next_token_opt(), None);
     assert_eq!(array.get_type(), de_array.get_type());
     assert_eq!(array.dims(), de_array.dims());
 
     let mut array_vec: Vec<f64> = vec![0f64; array.elements()];
-    array.host(&mut array_vec.as_mut_slice());
+    array.host(array_vec.as_mut_slice());
     let mut de_array_vec: Vec<f64> = vec![0f64; de_array.elements()];
-    de_array.host(&mut de_array_vec.as_mut_slice());
+    de_array.host(de_array_vec.as_mut_slice());
     assert_eq!(array_vec, de_array_vec);
 }
+
+#[test]
+fn test_array_fused() {
+    let dim = Dim4::new(&[2, 2, 1, 1]);
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("f32"),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(4) },
+        Token::F32(2.0),
+        Token::F32(4.0),
+        Token::F32(6.0),
+        Token::F32(8.0),
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    let direct = Array::new::<f32>(&[2.0, 4.0, 6.0, 8.0], dim);
+    assert_ser_tokens(&Ser::new(&direct).readable(), &tokens);
+
+    // an unevaluated JIT expression, and one built from a transposed operand
+    let ones = Array::new::<f32>(&[1.0, 1.0, 1.0, 1.0], dim);
+    let base = Array::new::<f32>(&[1.0, 2.0, 3.0, 4.0], dim);
+    let fused = &(&base + &base) * &ones;
+    assert_ser_tokens(&Ser::new(&fused).readable(), &tokens);
+
+    let transposed = Array::new::<f32>(&[1.0, 3.0, 2.0, 4.0], dim);
+    let fused = &transpose(&transposed, false) * &Array::new::<f32>(&[2.0; 4], dim);
+    assert_ser_tokens(&Ser::new(&fused).readable(), &tokens);
+}
+
+#[test]
+fn test_array_truncated() {
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("f64"),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::TupleEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    assert!(deserialize::<Array, _>((&mut de).readable()).is_err());
+}
+
+#[test]
+fn test_array_unknown_version() {
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U16(2),
+        Token::U8(2),
+        Token::Tuple { len: 4 },
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(1) },
+        Token::F64(1.0),
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    let err = deserialize::<Array, _>((&mut de).readable()).err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "unsupported array format version 2 (expected 1)"
+    );
+}
+
+#[test]
+fn test_array_u8() {
+    // a 2x2 single channel image
+    let dim = Dim4::new(&[2, 2, 1, 1]);
+    let values: [u8; 4] = [0, 64, 128, 255];
+    let array = Array::new::<u8>(&values, dim);
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("u8"),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(4) },
+        Token::U8(0),
+        Token::U8(64),
+        Token::U8(128),
+        Token::U8(255),
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&Ser::new(&array).readable(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let de_array = deserialize::<Array, _>((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(de_array.get_type(), DType::U8);
+    assert_eq!(de_array.dims(), dim);
+
+    let mut de_array_vec = vec![0u8; de_array.elements()];
+    de_array.host(de_array_vec.as_mut_slice());
+    assert_eq!(&values[..], &de_array_vec[..]);
+}
+
+#[test]
+fn test_array_u8_b8_distinct() {
+    let dim = Dim4::new(&[8, 1, 1, 1]);
+    let bytes = Array::new::<u8>(&[0, 1, 1, 0, 1, 0, 0, 1], dim);
+    let bits = Array::new::<bool>(&[false, true, true, false, true, false, false, true], dim);
+
+    // both are byte sized on the host, but the tags and element tokens differ
+    let tokens = |tag: u8, element: Token| {
+        vec![
+            Token::Tuple { len: 4 },
+            Token::U16(1),
+            Token::U8(tag),
+            Token::Tuple { len: 4 },
+            Token::U64(1),
+            Token::U64(1),
+            Token::U64(1),
+            Token::U64(1),
+            Token::TupleEnd,
+            Token::Seq { len: Some(1) },
+            element,
+            Token::SeqEnd,
+            Token::TupleEnd,
+        ]
+    };
+    let load = |tokens: Vec<Token>| {
+        let mut de = Deserializer::new(&tokens);
+        deserialize::<Array, _>((&mut de).readable()).map(|array| array.get_type())
+    };
+    assert_eq!(load(tokens(7, Token::U8(1))).unwrap(), DType::U8);
+    assert_eq!(load(tokens(4, Token::Bool(true))).unwrap(), DType::B8);
+    assert!(load(tokens(4, Token::U8(1))).is_err());
+    assert!(load(tokens(7, Token::Bool(true))).is_err());
+
+    // the binary format stores B8 bit packed, so reading bits as U8 runs out of data
+    for &(array, dtype) in &[(&bytes, DType::U8), (&bits, DType::B8)] {
+        let mut encoded = Vec::new();
+        write_array(&mut encoded, array).unwrap();
+        let de_array = read_array(&mut encoded.as_slice()).unwrap();
+        assert_eq!(de_array.get_type(), dtype);
+        assert_eq!(de_array.dims(), dim);
+    }
+    let mut encoded = Vec::new();
+    write_array(&mut encoded, &bits).unwrap();
+    encoded[7] = 7;
+    assert!(read_array(&mut encoded.as_slice()).is_err());
+}
+
+#[test]
+fn test_array_flat() {
+    let dim = Dim4::new(&[2, 2, 1, 1]);
+    let tokens = [
+        Token::Seq { len: Some(4) },
+        Token::F32(1.0),
+        Token::F32(2.0),
+        Token::F32(3.0),
+        Token::F32(4.0),
+        Token::SeqEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    let array = deserialize_flat(DType::F32, dim, (&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert!(arrays_equal(
+        &array,
+        &Array::new::<f32>(&[1.0, 2.0, 3.0, 4.0], dim)
+    ));
+
+    // the data has to match the given dims
+    let mut de = Deserializer::new(&tokens);
+    let err = deserialize_flat(DType::F32, Dim4::new(&[3, 1, 1, 1]), (&mut de).readable())
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        Error::LengthMismatch {
+            expected: 3,
+            found: 4,
+        }
+        .to_string()
+    );
+
+    let tokens = [Token::Seq { len: Some(0) }, Token::SeqEnd];
+    let mut de = Deserializer::new(&tokens);
+    let empty =
+        deserialize_flat(DType::U16, Dim4::new(&[0, 1, 1, 1]), (&mut de).readable()).unwrap();
+    assert_eq!(empty.elements(), 0);
+}
+
+#[test]
+fn test_array_u8_borrowed() {
+    let dim = Dim4::new(&[3, 1, 1, 1]);
+    let array = Array::new::<u8>(&[7, 0, 255], dim);
+    let tokens = |data: Token| {
+        vec![
+            Token::Tuple { len: 4 },
+            Token::U16(1),
+            Token::Str("u8"),
+            Token::Tuple { len: 4 },
+            Token::U64(3),
+            Token::U64(1),
+            Token::U64(1),
+            Token::U64(1),
+            Token::TupleEnd,
+            data,
+            Token::TupleEnd,
+        ]
+    };
+
+    // u8 data may be given as bytes, borrowed from the input or not
+    for &data in &[
+        Token::BorrowedBytes(&[7, 0, 255]),
+        Token::Bytes(&[7, 0, 255]),
+    ] {
+        let tokens = tokens(data);
+        let mut de = Deserializer::new(&tokens);
+        let de_array = deserialize::<Array, _>((&mut de).readable()).unwrap();
+        assert_eq!(de.next_token_opt(), None);
+        assert!(arrays_equal(&array, &de_array));
+    }
+
+    let tokens = tokens(Token::BorrowedBytes(&[7, 0]));
+    let mut de = Deserializer::new(&tokens);
+    assert_eq!(
+        deserialize::<Array, _>((&mut de).readable())
+            .err()
+            .unwrap()
+            .to_string(),
+        Error::LengthMismatch {
+            expected: 3,
+            found: 2,
+        }
+        .to_string()
+    );
+
+    // bincode hands out u8 sequences as borrowed bytes
+    #[cfg(feature = "bincode")]
+    {
+        let bytes = arrayfire_serde::to_bytes(&array).unwrap();
+        let de_array = arrayfire_serde::from_bytes(&bytes).unwrap();
+        assert!(arrays_equal(&array, &de_array));
+    }
+}
+
+#[test]
+fn test_array_length_mismatch() {
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("f64"),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(3) },
+        Token::F64(1.0),
+        Token::F64(2.0),
+        Token::F64(3.0),
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    let err = deserialize::<Array, _>((&mut de).readable()).err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "data length mismatch: expected 4 elements, found 3"
+    );
+}
+
+#[test]
+fn test_empty_array() {
+    let dim = Dim4::new(&[0, 3, 1, 1]);
+    let tokens = [
+        Token::Tuple { len: 3 },
+        Token::U16(1),
+        Token::Str("f32"),
+        Token::Tuple { len: 4 },
+        Token::U64(0),
+        Token::U64(3),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::TupleEnd,
+    ];
+
+    let mut de = Deserializer::new(&tokens);
+    let array = deserialize::<Array, _>((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(array.get_type(), DType::F32);
+    assert_eq!(array.dims(), dim);
+    assert_eq!(array.elements(), 0);
+
+    assert_ser_tokens(&Ser::new(&array).readable(), &tokens);
+}
+
+#[test]
+fn test_complex_array() {
+    let dim = Dim4::new(&[4, 1, 1, 1]);
+    let values: [f64; 4] = [1.0, 2.0, 3.0, 4.0];
+    let signal = Array::new::<f64>(&values, dim);
+    let array = fft(&signal, 1.0, 4);
+    assert_eq!(array.get_type(), DType::C64);
+
+    let mut array_vec = vec![Complex { re: 0f64, im: 0f64 }; array.elements()];
+    array.host(array_vec.as_mut_slice());
+
+    let mut tokens = vec![
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("c64"),
+        Token::Tuple { len: 4 },
+        Token::U64(4),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(4) },
+    ];
+    for c in &array_vec {
+        tokens.push(Token::Tuple { len: 2 });
+        tokens.push(Token::F64(c.re));
+        tokens.push(Token::F64(c.im));
+        tokens.push(Token::TupleEnd);
+    }
+    tokens.push(Token::SeqEnd);
+    tokens.push(Token::TupleEnd);
+    assert_ser_tokens(&Ser::new(&array).readable(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let de_array = deserialize::<Array, _>((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(array.get_type(), de_array.get_type());
+    assert_eq!(array.dims(), de_array.dims());
+
+    let mut de_array_vec = vec![Complex { re: 0f64, im: 0f64 }; de_array.elements()];
+    de_array.host(de_array_vec.as_mut_slice());
+    assert_eq!(array_vec, de_array_vec);
+}
+
+struct Human<'a>(&'a Array);
+
+impl<'a> Serialize for Human<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_human(self.0, serializer)
+    }
+}
+
+#[test]
+fn test_array_integer() {
+    let dim = Dim4::new(&[3, 1, 1, 1]);
+    let values: [i32; 3] = [-1, 0, 7];
+    let array = Array::new::<i32>(&values, dim);
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("s32"),
+        Token::Tuple { len: 4 },
+        Token::U64(3),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(3) },
+        Token::I32(-1),
+        Token::I32(0),
+        Token::I32(7),
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&Ser::new(&array).readable(), &tokens);
+}
+
+#[test]
+fn test_array_human() {
+    let dim = Dim4::new(&[2, 1, 1, 1]);
+    let values: [f32; 2] = [1.0, 2.0];
+    let array = Array::new::<f32>(&values, dim);
+    let tokens = [
+        Token::Struct {
+            name: "Array",
+            len: 4,
+        },
+        Token::Str("version"),
+        Token::U16(1),
+        Token::Str("dtype"),
+        Token::Str("f32"),
+        Token::Str("dims"),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Str("data"),
+        Token::Seq { len: Some(2) },
+        Token::F32(1.0),
+        Token::F32(2.0),
+        Token::SeqEnd,
+        Token::StructEnd,
+    ];
+    assert_ser_tokens(&Human(&array).readable(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let de_array = deserialize::<Array, _>((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(array.get_type(), de_array.get_type());
+    assert_eq!(array.dims(), de_array.dims());
+
+    let mut de_array_vec: Vec<f32> = vec![0f32; de_array.elements()];
+    de_array.host(de_array_vec.as_mut_slice());
+    assert_eq!(&values[..], &de_array_vec[..]);
+
+    // with `zerocopy` the compact form stores the data as bytes, see `test_array_zerocopy`
+    #[cfg(not(feature = "zerocopy"))]
+    {
+        let compact_tokens = [
+            Token::Tuple { len: 4 },
+            Token::U16(1),
+            Token::U8(0),
+            Token::Tuple { len: 4 },
+            Token::U64(2),
+            Token::U64(1),
+            Token::U64(1),
+            Token::U64(1),
+            Token::TupleEnd,
+            Token::Seq { len: Some(2) },
+            Token::F32(1.0),
+            Token::F32(2.0),
+            Token::SeqEnd,
+            Token::TupleEnd,
+        ];
+        assert_ser_tokens(&Human(&array).compact(), &compact_tokens);
+    }
+}
+
+#[test]
+fn test_array_fields_reordered() {
+    let dim = Dim4::new(&[2, 1, 1, 1]);
+    let field_tokens = |field: &'static str| match field {
+        "version" => vec![Token::Str("version"), Token::U16(1)],
+        "dtype" => vec![Token::Str("dtype"), Token::U8(8)],
+        "dims" => vec![
+            Token::Str("dims"),
+            Token::Tuple { len: 4 },
+            Token::U64(2),
+            Token::U64(1),
+            Token::U64(1),
+            Token::U64(1),
+            Token::TupleEnd,
+        ],
+        _ => vec![
+            Token::Str("data"),
+            Token::Seq { len: Some(2) },
+            Token::I64(-1),
+            Token::I64(7),
+            Token::SeqEnd,
+        ],
+    };
+    let map_tokens = |fields: &[&'static str]| {
+        let mut tokens = vec![Token::Map {
+            len: Some(fields.len()),
+        }];
+        for &field in fields {
+            tokens.extend(field_tokens(field));
+        }
+        tokens.push(Token::MapEnd);
+        tokens
+    };
+    let load = |fields: &[&'static str]| {
+        let tokens = map_tokens(fields);
+        let mut de = Deserializer::new(&tokens);
+        deserialize::<Array, _>((&mut de).readable()).map_err(|e| e.to_string())
+    };
+    let expected = Array::new::<i64>(&[-1, 7], dim);
+
+    // data after dtype and dims, and data first, buffered until both are known
+    let de_array = load(&["dims", "data", "version", "dtype"]).unwrap();
+    assert!(arrays_equal(&expected, &de_array));
+    let de_array = load(&["data", "dtype", "dims", "version"]).unwrap();
+    assert!(arrays_equal(&expected, &de_array));
+    let de_array = load(&["dtype", "dims", "data", "version"]).unwrap();
+    assert!(arrays_equal(&expected, &de_array));
+
+    assert_eq!(
+        load(&["data", "dims", "version"]).err().unwrap(),
+        "missing field `dtype`"
+    );
+    assert_eq!(
+        load(&["dtype", "dims", "version"]).err().unwrap(),
+        "missing field `data`"
+    );
+    assert_eq!(
+        load(&["dtype", "dims", "data", "version", "dims"])
+            .err()
+            .unwrap(),
+        "duplicate field `dims`"
+    );
+
+    // `data` may be omitted for empty arrays only
+    let tokens = [
+        Token::Map { len: Some(3) },
+        Token::Str("dims"),
+        Token::Tuple { len: 4 },
+        Token::U64(0),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Str("dtype"),
+        Token::U8(8),
+        Token::Str("version"),
+        Token::U16(1),
+        Token::MapEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    let de_array = deserialize::<Array, _>((&mut de).readable()).unwrap();
+    assert_eq!(de_array.elements(), 0);
+    assert_eq!(de_array.get_type(), DType::S64);
+
+    // the other visitors of the struct form share the same field handling
+    for fields in &[
+        ["dims", "data", "version", "dtype"],
+        ["data", "dtype", "dims", "version"],
+    ] {
+        let tokens = map_tokens(fields);
+
+        // into an array of the same shape, and into one of another shape
+        for place in &[
+            Array::new::<i64>(&[0, 0], dim),
+            Array::new::<f32>(&[0.0], Dim4::new(&[1, 1, 1, 1])),
+        ] {
+            let mut place = place.copy();
+            let mut de = Deserializer::new(&tokens);
+            deserialize_in_place((&mut de).readable(), &mut place).unwrap();
+            assert!(arrays_equal(&expected, &place));
+        }
+
+        let mut de = Deserializer::new(&tokens);
+        let (dtype, dims) = deserialize_header((&mut de).readable()).unwrap();
+        assert_eq!(dtype, DType::S64);
+        assert_eq!(dims, dim);
+
+        let mut de = Deserializer::new(&tokens);
+        let de_array = deserialize_legacy((&mut de).readable()).unwrap();
+        assert!(arrays_equal(&expected, &de_array));
+    }
+}
+
+#[test]
+fn test_backend() {
+    let backend = Backend::CUDA;
+    let tokens = [Token::U8(2)];
+    assert_ser_tokens(&Ser::new(&backend), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let deserialized = deserialize::<Backend, _>(&mut de).unwrap();
+    assert_eq!(deserialized, backend);
+    assert_eq!(de.next_token_opt(), None);
+}
+
+struct Full<'a>(&'a Array);
+
+impl<'a> Serialize for Full<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_full(self.0, serializer)
+    }
+}
+
+#[test]
+fn test_array_full() {
+    let dim = Dim4::new(&[1, 1, 1, 1]);
+    let values: [u32; 1] = [42];
+    let array = Array::new::<u32>(&values, dim);
+    let backend = get_active_backend();
+    let tokens = [
+        Token::Tuple { len: 2 },
+        Token::U8(backend as u8),
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("u32"),
+        Token::Tuple { len: 4 },
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(1) },
+        Token::U32(42),
+        Token::SeqEnd,
+        Token::TupleEnd,
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&Full(&array).readable(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let full = deserialize_full((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(full.backend, backend);
+    assert_eq!(full.array.get_type(), DType::U32);
+    assert_eq!(full.array.dims(), dim);
+}
+
+struct WithLayout<'a>(&'a Array);
+
+impl<'a> Serialize for WithLayout<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_with_layout(self.0, serializer)
+    }
+}
+
+#[test]
+fn test_array_layout() {
+    let dim = Dim4::new(&[3, 1, 1, 1]);
+    let column = Array::new::<f32>(&[1.0, 2.0, 3.0], dim);
+    assert_eq!(
+        Layout::of(&column).unwrap(),
+        Layout {
+            vector: true,
+            row: false,
+            column: true,
+        }
+    );
+    let tokens = |layout: u8| {
+        vec![
+            Token::Tuple { len: 2 },
+            Token::U8(layout),
+            Token::Tuple { len: 4 },
+            Token::U16(1),
+            Token::Str("f32"),
+            Token::Tuple { len: 4 },
+            Token::U64(3),
+            Token::U64(1),
+            Token::U64(1),
+            Token::U64(1),
+            Token::TupleEnd,
+            Token::Seq { len: Some(3) },
+            Token::F32(1.0),
+            Token::F32(2.0),
+            Token::F32(3.0),
+            Token::SeqEnd,
+            Token::TupleEnd,
+            Token::TupleEnd,
+        ]
+    };
+    let column_tokens = tokens(0b101);
+    assert_ser_tokens(&WithLayout(&column).readable(), &column_tokens);
+
+    let mut de = Deserializer::new(&column_tokens);
+    let de_array = deserialize_with_layout((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert!(arrays_equal(&column, &de_array));
+
+    // a column stored as a row vector
+    let row_tokens = tokens(0b011);
+    let mut de = Deserializer::new(&row_tokens);
+    let err = deserialize_with_layout((&mut de).readable()).err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        Error::LayoutMismatch {
+            expected: Layout {
+                vector: true,
+                row: true,
+                column: false,
+            },
+            found: Layout::of(&column).unwrap(),
+        }
+        .to_string()
+    );
+}
+
+struct Image<'a>(&'a Array);
+
+impl<'a> Serialize for Image<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_image(self.0, serializer)
+    }
+}
+
+#[test]
+fn test_array_image() {
+    let tokens = |header: [u64; 3], dims: [u64; 3], data: &[u8]| {
+        let mut tokens = vec![
+            Token::Tuple { len: 2 },
+            Token::Tuple { len: 3 },
+            Token::U64(header[0]),
+            Token::U64(header[1]),
+            Token::U8(header[2] as u8),
+            Token::TupleEnd,
+            Token::Tuple { len: 4 },
+            Token::U16(1),
+            Token::Str("u8"),
+            Token::Tuple { len: 4 },
+            Token::U64(dims[0]),
+            Token::U64(dims[1]),
+            Token::U64(dims[2]),
+            Token::U64(1),
+            Token::TupleEnd,
+            Token::Seq {
+                len: Some(data.len()),
+            },
+        ];
+        tokens.extend(data.iter().map(|&value| Token::U8(value)));
+        tokens.extend(vec![Token::SeqEnd, Token::TupleEnd, Token::TupleEnd]);
+        tokens
+    };
+
+    for &channels in &[1u64, 3, 4] {
+        let data: Vec<u8> = (0..2 * channels as u8).collect();
+        let image = Array::new::<u8>(&data, Dim4::new(&[2, 1, channels, 1]));
+        let image_tokens = tokens([2, 1, channels], [2, 1, channels], &data);
+        assert_ser_tokens(&Image(&image).readable(), &image_tokens);
+
+        let mut de = Deserializer::new(&image_tokens);
+        let de_image = deserialize_image((&mut de).readable()).unwrap();
+        assert_eq!(de.next_token_opt(), None);
+        assert!(arrays_equal(&image, &de_image));
+    }
+
+    // two channels, a fourth dim and a non-U8 dtype are rejected before any data is written
+    let not_an_image = |dims: [u64; 4]| Error::NotAnImage { dims }.to_string();
+    let two_channels = Array::new::<u8>(&[0, 1, 2, 3], Dim4::new(&[2, 1, 2, 1]));
+    assert_ser_tokens_error(
+        &Image(&two_channels).readable(),
+        &[],
+        &not_an_image([2, 1, 2, 1]),
+    );
+    let batch = Array::new::<u8>(&[0, 1], Dim4::new(&[1, 1, 1, 2]));
+    assert_ser_tokens_error(&Image(&batch).readable(), &[], &not_an_image([1, 1, 1, 2]));
+    let floats = Array::new::<f32>(&[0.0, 1.0], Dim4::new(&[2, 1, 1, 1]));
+    assert_ser_tokens_error(
+        &Image(&floats).readable(),
+        &[],
+        &Error::DTypeMismatch {
+            expected: DType::U8,
+            found: DType::F32,
+        }
+        .to_string(),
+    );
+
+    let load = |tokens: Vec<Token>| {
+        let mut de = Deserializer::new(&tokens);
+        deserialize_image((&mut de).readable())
+            .err()
+            .unwrap()
+            .to_string()
+    };
+    assert_eq!(
+        load(tokens([2, 1, 2], [2, 1, 2], &[0, 1, 2, 3])),
+        not_an_image([2, 1, 2, 1])
+    );
+    assert_eq!(
+        load(tokens([1, 2, 1], [2, 1, 1], &[0, 1])),
+        "image header (1, 2, 1) does not match array dims [2, 1, 1, 1]"
+    );
+}
+
+struct Meta<'a>(&'a Array);
+
+impl<'a> Serialize for Meta<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_meta(self.0, serializer)
+    }
+}
+
+#[test]
+fn test_array_meta() {
+    let dim = Dim4::new(&[2, 3, 1, 1]);
+    let array = Array::new::<f64>(&[0.0; 6], dim);
+    // no data element follows the element count
+    let tokens = [
+        Token::Tuple { len: 3 },
+        Token::Str("f64"),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(3),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::U64(6),
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&Meta(&array).readable(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let meta = deserialize_meta((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(
+        meta,
+        ArrayMeta {
+            dtype: DType::F64,
+            dims: dim,
+        }
+    );
+    assert_eq!(meta, ArrayMeta::of(&array).unwrap());
+    assert_eq!(meta.elements().unwrap(), 6);
+
+    let mut bad_tokens = tokens.to_vec();
+    bad_tokens[8] = Token::U64(5);
+    let mut de = Deserializer::new(&bad_tokens);
+    assert_eq!(
+        deserialize_meta((&mut de).readable())
+            .err()
+            .unwrap()
+            .to_string(),
+        "element count 5 does not match dims [2, 3, 1, 1]"
+    );
+}
+
+#[cfg(feature = "bincode")]
+#[test]
+fn test_bytes() {
+    let dim = Dim4::new(&[2, 2, 1, 1]);
+    let values: [f32; 4] = [1.0, 2.0, 3.0, 4.0];
+    let array = Array::new::<f32>(&values, dim);
+
+    let bytes = arrayfire_serde::to_bytes(&array).unwrap();
+    let de_array = arrayfire_serde::from_bytes(&bytes).unwrap();
+    assert_eq!(array.get_type(), de_array.get_type());
+    assert_eq!(array.dims(), de_array.dims());
+
+    let mut de_array_vec: Vec<f32> = vec![0f32; de_array.elements()];
+    de_array.host(de_array_vec.as_mut_slice());
+    assert_eq!(&values[..], &de_array_vec[..]);
+}
+
+struct Packed<'a>(&'a Array);
+
+impl<'a> Serialize for Packed<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_packed(self.0, serializer)
+    }
+}
+
+#[cfg(target_endian = "little")]
+#[test]
+fn test_array_packed() {
+    let dim = Dim4::new(&[2, 1, 1, 1]);
+    let values: [f32; 2] = [1.0, 2.0];
+    let array = Array::new::<f32>(&values, dim);
+    let tokens = [
+        Token::Tuple { len: 5 },
+        Token::U16(1),
+        Token::U8(0),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::U8(0),
+        Token::Bytes(&[0x00, 0x00, 0x80, 0x3f, 0x00, 0x00, 0x00, 0x40]),
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&Packed(&array).compact(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let de_array = deserialize_packed((&mut de).compact()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(array.get_type(), de_array.get_type());
+    assert_eq!(array.dims(), de_array.dims());
+
+    let mut de_array_vec: Vec<f32> = vec![0f32; de_array.elements()];
+    de_array.host(de_array_vec.as_mut_slice());
+    assert_eq!(&values[..], &de_array_vec[..]);
+}
+
+#[test]
+fn test_array_packed_length_mismatch() {
+    let tokens = [
+        Token::Tuple { len: 5 },
+        Token::U16(1),
+        Token::U8(0),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::U8(0),
+        Token::Bytes(&[0x00, 0x00, 0x80, 0x3f]),
+        Token::TupleEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    let err = deserialize_packed((&mut de).compact()).err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "data length mismatch: expected 2 elements, found 1"
+    );
+}
+
+fn packed_tokens(dtype: u8, endianness: u8, bytes: &'static [u8]) -> Vec<Token> {
+    vec![
+        Token::Tuple { len: 5 },
+        Token::U16(1),
+        Token::U8(dtype),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::U8(endianness),
+        Token::Bytes(bytes),
+        Token::TupleEnd,
+    ]
+}
+
+fn deserialize_packed_host<T: Clone + Default + arrayfire::HasAfEnum>(tokens: &[Token]) -> Vec<T> {
+    let mut de = Deserializer::new(tokens);
+    let array = deserialize_packed((&mut de).compact()).unwrap();
+    let mut data = vec![T::default(); array.elements()];
+    array.host(data.as_mut_slice());
+    data
+}
+
+#[cfg(target_endian = "little")]
+#[test]
+fn test_array_packed_byte_swapped() {
+    let tokens = packed_tokens(0, 1, &[0x3f, 0x80, 0x00, 0x00, 0xc0, 0x20, 0x00, 0x00]);
+    assert_eq!(deserialize_packed_host::<f32>(&tokens), vec![1.0, -2.5]);
+
+    let tokens = packed_tokens(
+        2,
+        1,
+        &[
+            0x3f, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3f, 0xe0, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00,
+        ],
+    );
+    assert_eq!(deserialize_packed_host::<f64>(&tokens), vec![1.0, 0.5]);
+
+    let tokens = packed_tokens(5, 1, &[0x00, 0x00, 0x00, 0x01, 0xff, 0xff, 0xff, 0xfe]);
+    assert_eq!(deserialize_packed_host::<i32>(&tokens), vec![1, -2]);
+
+    let tokens = packed_tokens(
+        9,
+        1,
+        &[
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x02, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00,
+        ],
+    );
+    assert_eq!(deserialize_packed_host::<u64>(&tokens), vec![258, 1 << 56]);
+}
+
+#[test]
+fn test_array_packed_invalid_endianness() {
+    let tokens = packed_tokens(0, 2, &[0x00, 0x00, 0x80, 0x3f, 0x00, 0x00, 0x00, 0x40]);
+    let mut de = Deserializer::new(&tokens);
+    let err = deserialize_packed((&mut de).compact()).err().unwrap();
+    assert_eq!(err.to_string(), "invalid endianness marker 2");
+}
+
+struct Batch<'a>(&'a [Array]);
+
+impl<'a> Serialize for Batch<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_seq(self.0, serializer)
+    }
+}
+
+#[test]
+fn test_array_seq() {
+    let dim = Dim4::new(&[2, 1, 1, 1]);
+    let batch = vec![
+        Array::new::<f64>(&[1.0, 2.0], dim),
+        Array::new::<i32>(&[3, -4], dim),
+    ];
+    let tokens = [
+        Token::Seq { len: Some(2) },
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("f64"),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(2) },
+        Token::F64(1.0),
+        Token::F64(2.0),
+        Token::SeqEnd,
+        Token::TupleEnd,
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("s32"),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(2) },
+        Token::I32(3),
+        Token::I32(-4),
+        Token::SeqEnd,
+        Token::TupleEnd,
+        Token::SeqEnd,
+    ];
+    assert_ser_tokens(&Batch(&batch).readable(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let de_batch = deserialize_seq::<Array, _>((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(de_batch.len(), 2);
+    assert_eq!(de_batch[0].get_type(), DType::F64);
+    assert_eq!(de_batch[1].get_type(), DType::S32);
+
+    let mut floats = vec![0f64; 2];
+    de_batch[0].host(floats.as_mut_slice());
+    assert_eq!(floats, vec![1.0, 2.0]);
+    let mut ints = vec![0i32; 2];
+    de_batch[1].host(ints.as_mut_slice());
+    assert_eq!(ints, vec![3, -4]);
+}
+
+#[test]
+fn test_serde_vec() {
+    let dim = Dim4::new(&[1, 1, 1, 1]);
+    let arrays = vec![
+        Serde(Array::new::<f32>(&[1.0], dim)),
+        Serde(Array::new::<u32>(&[2], dim)),
+    ];
+    let tokens = [
+        Token::Seq { len: Some(2) },
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("f32"),
+        Token::Tuple { len: 4 },
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(1) },
+        Token::F32(1.0),
+        Token::SeqEnd,
+        Token::TupleEnd,
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("u32"),
+        Token::Tuple { len: 4 },
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(1) },
+        Token::U32(2),
+        Token::SeqEnd,
+        Token::TupleEnd,
+        Token::SeqEnd,
+    ];
+    assert_ser_tokens(&arrays.readable(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let de_arrays = Vec::<Serde<Array>>::deserialize((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(de_arrays.len(), 2);
+    // fields of the inner array are reachable through `Deref`
+    assert_eq!(de_arrays[0].get_type(), DType::F32);
+    assert_eq!(de_arrays[1].get_type(), DType::U32);
+    assert_eq!(de_arrays[1].dims(), dim);
+}
+
+#[test]
+fn test_array_packed_bits() {
+    let dim = Dim4::new(&[13, 1, 1, 1]);
+    let values: Vec<bool> = (0..13).map(|i| i % 3 == 0).collect();
+    let array = Array::new::<bool>(&values, dim);
+    let tokens = [
+        Token::Tuple { len: 5 },
+        Token::U16(1),
+        Token::U8(4),
+        Token::Tuple { len: 4 },
+        Token::U64(13),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::U8(if cfg!(target_endian = "big") { 1 } else { 0 }),
+        Token::Bytes(&[0x49, 0x12]),
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&Packed(&array).compact(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let de_array = deserialize_packed((&mut de).compact()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(de_array.get_type(), DType::B8);
+    assert_eq!(de_array.dims(), dim);
+
+    let mut de_array_vec = vec![false; de_array.elements()];
+    de_array.host(de_array_vec.as_mut_slice());
+    assert_eq!(values, de_array_vec);
+}
+
+#[test]
+fn test_array_file() {
+    use std::io::{Seek, SeekFrom};
+
+    let dim = Dim4::new(&[2, 3, 1, 1]);
+    let values: [f64; 6] = [1.0, -2.0, 3.5, 4.0, 5.25, -6.0];
+    let array = Array::new::<f64>(&values, dim);
+
+    let mut file = tempfile::tempfile().unwrap();
+    write_array(&mut file, &array).unwrap();
+    assert_eq!(file.metadata().unwrap().len(), 40 + 6 * 8);
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+    let de_array = read_array(&mut file).unwrap();
+    assert_eq!(de_array.get_type(), DType::F64);
+    assert_eq!(de_array.dims(), dim);
+
+    let mut de_array_vec = vec![0f64; de_array.elements()];
+    de_array.host(de_array_vec.as_mut_slice());
+    assert_eq!(&values[..], &de_array_vec[..]);
+}
+
+#[test]
+fn test_array_file_bad_header() {
+    let array = Array::new::<u8>(&[1, 2], Dim4::new(&[2, 1, 1, 1]));
+    let mut bytes = Vec::new();
+    write_array(&mut bytes, &array).unwrap();
+    let read = |bytes: &[u8]| read_array(&mut &bytes[..]).err().unwrap().to_string();
+
+    let mut bad_magic = bytes.clone();
+    bad_magic[..4].copy_from_slice(b"PK\x03\x04");
+    assert_eq!(
+        read(&bad_magic),
+        Error::BadMagic {
+            found: *b"PK\x03\x04",
+        }
+        .to_string()
+    );
+
+    let mut bad_version = bytes.clone();
+    bad_version[4..6].copy_from_slice(&7u16.to_le_bytes());
+    assert_eq!(
+        read(&bad_version),
+        "format error: unsupported array format version 7 (expected 1)"
+    );
+
+    let mut bad_flags = bytes.clone();
+    bad_flags[6] = 0x80;
+    assert_eq!(read(&bad_flags), "format error: unknown flags 0x80");
+}
+
+#[test]
+fn test_array_stream() {
+    use std::io::{Seek, SeekFrom};
+
+    let arrays = [
+        Array::new::<f32>(&[1.0, 2.0, 3.0], Dim4::new(&[3, 1, 1, 1])),
+        Array::new::<i32>(&[4, 5, 6, 7], Dim4::new(&[2, 2, 1, 1])),
+        Array::new::<bool>(&[true, false], Dim4::new(&[1, 2, 1, 1])),
+    ];
+
+    let mut writer = ArrayWriter::new(tempfile::tempfile().unwrap());
+    for array in &arrays {
+        writer.push(array).unwrap();
+    }
+    let mut file = writer.into_inner();
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+    let de_arrays: Vec<Array> = ArrayReader::new(&mut file)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(de_arrays.len(), 3);
+    for (array, de_array) in arrays.iter().zip(&de_arrays) {
+        assert!(arrays_equal(array, de_array));
+    }
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+    let mut reader = ArrayReader::new(&mut file);
+    assert!(reader.skip_record().unwrap());
+    assert!(reader.skip_record().unwrap());
+    let last = reader.next().unwrap().unwrap();
+    assert_eq!(last.get_type(), DType::B8);
+    assert!(reader.next().is_none());
+}
+
+/// Serializes `value` by value through `arrayfire_serde::serialize` from generic code.
+fn assert_owned_ser_tokens<T>(value: T, tokens: &[Token])
+where
+    for<'a> Ser<'a, T>: Serialize,
+{
+    struct Owned<T>(T);
+
+    impl<T> Serialize for Owned<T>
+    where
+        for<'a> Ser<'a, T>: Serialize,
+    {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serialize(&self.0, serializer)
+        }
+    }
+
+    assert_ser_tokens(&Owned(value).readable(), tokens);
+}
+
+#[test]
+fn test_array_owned() {
+    let dim = Dim4::new(&[2, 1, 1, 1]);
+    let mut array = Array::new::<f32>(&[1.0, 2.0], dim);
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("f32"),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(2) },
+        Token::F32(1.0),
+        Token::F32(2.0),
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&Ser::new(&&array).readable(), &tokens);
+    assert_ser_tokens(&Ser::new(&&mut array).readable(), &tokens);
+    assert_owned_ser_tokens(array, &tokens);
+}
+
+#[test]
+fn test_array_ref_ref() {
+    let a = Array::new::<f32>(&[1.0, 2.0], Dim4::new(&[2, 1, 1, 1]));
+    let b = Array::new::<i32>(&[3, 4, 5], Dim4::new(&[1, 3, 1, 1]));
+    let arrays: Vec<&Array> = vec![&a, &b];
+    // iterating yields `&&Array`
+    let json: Vec<String> = arrays
+        .iter()
+        .map(|array| serde_json::to_string(&Ser::new(array)).unwrap())
+        .collect();
+    assert_eq!(
+        json,
+        [
+            serde_json::to_string(&Ser::new(&a)).unwrap(),
+            serde_json::to_string(&Ser::new(&b)).unwrap(),
+        ]
+    );
+}
+
+#[cfg(feature = "serde_with")]
+#[test]
+fn test_array_serde_as() {
+    use arrayfire_serde::ArrayAsTuple;
+    use serde_with::serde_as;
+
+    #[serde_as]
+    #[derive(Serialize, Deserialize)]
+    struct Model {
+        #[serde_as(as = "ArrayAsTuple")]
+        weights: Array,
+        #[serde_as(as = "Option<Vec<ArrayAsTuple>>")]
+        layers: Option<Vec<Array>>,
+    }
+
+    let weights = Array::new(&[1.5f32, -2.0], Dim4::new(&[2, 1, 1, 1]));
+    let layer = Array::new(&[1i32, 2, 3, 4], Dim4::new(&[2, 2, 1, 1]));
+    let model = Model {
+        weights: weights.copy(),
+        layers: Some(vec![layer.copy()]),
+    };
+
+    let json = serde_json::to_string(&model).unwrap();
+    assert_eq!(
+        json,
+        format!(
+            r#"{{"weights":{},"layers":[{}]}}"#,
+            serde_json::to_string(&Ser::new(&weights)).unwrap(),
+            serde_json::to_string(&Ser::new(&layer)).unwrap()
+        )
+    );
+    let de_model: Model = serde_json::from_str(&json).unwrap();
+    assert!(arrays_equal(&de_model.weights, &weights));
+    let de_layers = de_model.layers.unwrap();
+    assert_eq!(de_layers.len(), 1);
+    assert!(arrays_equal(&de_layers[0], &layer));
+}
+
+#[cfg(feature = "logging")]
+#[test]
+fn test_array_logging() {
+    use std::sync::Mutex;
+
+    struct CapturingLogger(Mutex<Vec<String>>);
+
+    impl log::Log for CapturingLogger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            if record.target().starts_with("arrayfire_serde") {
+                self.0.lock().unwrap().push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CapturingLogger = CapturingLogger(Mutex::new(Vec::new()));
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Info);
+    arrayfire_serde::set_log_threshold(4000);
+
+    // other tests may log concurrently, so only records of these sizes are checked
+    let large = Array::new(&vec![0.5f64; 501], Dim4::new(&[3, 167, 1, 1]));
+    let small = Array::new(&vec![0.5f64; 499], Dim4::new(&[499, 1, 1, 1]));
+    for array in &[&large, &small] {
+        let json = serde_json::to_string(&Ser::new(*array)).unwrap();
+        let mut expected = format!("[1,\"f64\",[{},{},1,1],[", array.dims()[0], array.dims()[1]);
+        expected.push_str(&vec!["0.5"; array.elements()].join(","));
+        expected.push_str("]]");
+        assert_eq!(json, expected);
+        let de_array = serde_json::from_str::<Serde<Array>>(&json).unwrap();
+        assert!(arrays_equal(array, &de_array));
+    }
+
+    let records: Vec<String> = LOGGER
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|record| record.contains(" 501 elements") || record.contains(" 499 elements"))
+        .cloned()
+        .collect();
+    assert_eq!(
+        records,
+        [
+            "serializing F64 array of 501 elements (4008 bytes)",
+            "deserialized F64 array of 501 elements (4008 bytes)",
+        ]
+    );
+}
+
+#[cfg(feature = "arrow")]
+#[test]
+fn test_to_arrow_buffer() {
+    use arrow_buffer::{BooleanBuffer, ScalarBuffer};
+    use arrow_schema::DataType;
+
+    let floats = Array::new(&[1.5f64, -2.0, 0.25], Dim4::new(&[3, 1, 1, 1]));
+    let (data_type, buffer) = arrayfire_serde::to_arrow_buffer(&floats).unwrap();
+    assert_eq!(data_type, DataType::Float64);
+    let values = ScalarBuffer::<f64>::new(buffer, 0, 3);
+    assert_eq!(values.len(), 3);
+    assert_eq!(values[..2], [1.5, -2.0]);
+
+    // column-major, so the first column comes first
+    let ints = Array::new(&[1i32, 2, 3, 4, 5, 6], Dim4::new(&[2, 3, 1, 1]));
+    let (data_type, buffer) = arrayfire_serde::to_arrow_buffer(&ints).unwrap();
+    assert_eq!(data_type, DataType::Int32);
+    let values = ScalarBuffer::<i32>::new(buffer, 0, 6);
+    assert_eq!(values.len(), 6);
+    assert_eq!(values[..3], [1, 2, 3]);
+
+    let bits = Array::new(&[true, false, true, true], Dim4::new(&[4, 1, 1, 1]));
+    let (data_type, buffer) = arrayfire_serde::to_arrow_buffer(&bits).unwrap();
+    assert_eq!(data_type, DataType::Boolean);
+    let values = BooleanBuffer::new(buffer, 0, 4);
+    assert_eq!(values.len(), 4);
+    assert_eq!(values.iter().collect::<Vec<_>>(), [true, false, true, true]);
+
+    let empty = Array::new_empty(Dim4::new(&[0, 1, 1, 1]), DType::U16);
+    let (data_type, buffer) = arrayfire_serde::to_arrow_buffer(&empty).unwrap();
+    assert_eq!(data_type, DataType::UInt16);
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn test_arrays_equal() {
+    let dim = Dim4::new(&[2, 2, 1, 1]);
+    let ints = Array::new::<i32>(&[1, 2, 3, 4], dim);
+    assert!(arrays_equal(&ints, &Array::new::<i32>(&[1, 2, 3, 4], dim)));
+    assert!(!arrays_equal(&ints, &Array::new::<i32>(&[1, 2, 3, 5], dim)));
+    // same data, different dtype or dims
+    assert!(!arrays_equal(&ints, &Array::new::<u32>(&[1, 2, 3, 4], dim)));
+    assert!(!arrays_equal(
+        &ints,
+        &Array::new::<i32>(&[1, 2, 3, 4], Dim4::new(&[4, 1, 1, 1]))
+    ));
+
+    // floats compare bit for bit
+    let nan = Array::new::<f64>(&[f64::NAN, 1.0], Dim4::new(&[2, 1, 1, 1]));
+    assert!(arrays_equal(&nan, &nan));
+    let zero = Array::new::<f32>(&[0.0], Dim4::new(&[1, 1, 1, 1]));
+    let neg_zero = Array::new::<f32>(&[-0.0], Dim4::new(&[1, 1, 1, 1]));
+    assert!(arrays_equal(&zero, &zero));
+    assert!(!arrays_equal(&zero, &neg_zero));
+
+    let c = Complex {
+        re: 1.0f32,
+        im: -1.0,
+    };
+    let complex = Array::new(&[c], Dim4::new(&[1, 1, 1, 1]));
+    assert!(arrays_equal(
+        &complex,
+        &Array::new(&[c], Dim4::new(&[1, 1, 1, 1]))
+    ));
+    assert!(!arrays_equal(
+        &complex,
+        &Array::new(&[c.conj()], Dim4::new(&[1, 1, 1, 1]))
+    ));
+
+    let bits = Array::new::<bool>(&[true, false, true], Dim4::new(&[3, 1, 1, 1]));
+    assert!(arrays_equal(&bits, &bits.copy()));
+    assert!(!arrays_equal(
+        &bits,
+        &Array::new::<bool>(&[true, true, true], Dim4::new(&[3, 1, 1, 1]))
+    ));
+}
+
+#[test]
+fn test_arrays_approx_equal() {
+    let dim = Dim4::new(&[2, 1, 1, 1]);
+    let c32 = Array::new(&[Complex::new(1.0f32, -2.0), Complex::new(0.5, 3.0)], dim);
+    let c32_close = Array::new(
+        &[Complex::new(1.0f32, -2.0005), Complex::new(0.5004, 3.0)],
+        dim,
+    );
+    assert!(arrays_approx_equal(&c32, &c32, 0.0));
+    assert!(arrays_approx_equal(&c32, &c32_close, 1e-3));
+    assert!(!arrays_approx_equal(&c32, &c32_close, 1e-4));
+    assert!(!arrays_equal(&c32, &c32_close));
+
+    // the imaginary part alone decides
+    let c64 = Array::new(&[Complex::new(1.0f64, 1.0), Complex::new(2.0, 2.0)], dim);
+    let c64_im = Array::new(&[Complex::new(1.0f64, 1.0), Complex::new(2.0, 2.5)], dim);
+    assert!(!arrays_approx_equal(&c64, &c64_im, 0.1));
+    assert!(arrays_approx_equal(&c64, &c64_im, 0.5));
+    let c64_nan = Array::new(
+        &[
+            Complex::new(f64::NAN, 1.0),
+            Complex::new(2.0, f64::INFINITY),
+        ],
+        dim,
+    );
+    assert!(arrays_approx_equal(&c64_nan, &c64_nan, 0.0));
+    assert!(!arrays_approx_equal(&c64_nan, &c64, f64::MAX));
+
+    // dtypes and dims have to match exactly
+    let c32_as_c64 = Array::new(&[Complex::new(1.0f64, -2.0), Complex::new(0.5, 3.0)], dim);
+    assert!(!arrays_approx_equal(&c32, &c32_as_c64, 1.0));
+    let c32_row = Array::new(
+        &[Complex::new(1.0f32, -2.0), Complex::new(0.5, 3.0)],
+        Dim4::new(&[1, 2, 1, 1]),
+    );
+    assert!(!arrays_approx_equal(&c32, &c32_row, 1.0));
+
+    let floats = Array::new::<f32>(&[1.0, 2.0], dim);
+    assert!(arrays_approx_equal(
+        &floats,
+        &Array::new::<f32>(&[1.01, 1.99], dim),
+        0.02
+    ));
+    let ints = Array::new::<i32>(&[1, 2], dim);
+    assert!(!arrays_approx_equal(
+        &ints,
+        &Array::new::<i32>(&[1, 3], dim),
+        10.0
+    ));
+
+    for &dtype in &[DType::F32, DType::C64] {
+        let empty = Array::new_empty(Dim4::new(&[0, 3, 1, 1]), dtype);
+        let same = Array::new_empty(Dim4::new(&[0, 3, 1, 1]), dtype);
+        assert!(arrays_approx_equal(&empty, &same, 0.0));
+    }
+}
+
+#[test]
+fn test_array_lazy() {
+    let dim = Dim4::new(&[2, 1, 1, 1]);
+    let a = Array::new::<f32>(&[1.0, 2.0], dim);
+    let b = Array::new::<f32>(&[0.5, 0.25], dim);
+    let sum = &a + &b;
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("f32"),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(2) },
+        Token::F32(1.5),
+        Token::F32(2.25),
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&Ser::new(&sum).readable(), &tokens);
+}
+
+#[test]
+fn test_array_view() {
+    let dim = Dim4::new(&[3, 3, 1, 1]);
+    let values: Vec<i32> = (0..9).collect();
+    let array = Array::new::<i32>(&values, dim);
+    // rows 1..=2 of columns 0..=1
+    let view = index(&array, &[Seq::new(1.0, 2.0, 1.0), Seq::new(0.0, 1.0, 1.0)]);
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("s32"),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(4) },
+        Token::I32(1),
+        Token::I32(2),
+        Token::I32(4),
+        Token::I32(5),
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&Ser::new(&view).readable(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let de_array = deserialize::<Array, _>((&mut de).readable()).unwrap();
+    let mut view_vec = vec![0i32; view.elements()];
+    view.host(view_vec.as_mut_slice());
+    let mut de_array_vec = vec![0i32; de_array.elements()];
+    de_array.host(de_array_vec.as_mut_slice());
+    assert_eq!(view_vec, de_array_vec);
+    assert_eq!(de_array.offset(), 0);
+    assert!(de_array.is_linear());
+
+    // the same holds for a view offset into its parent along the last dims
+    let column = index(&array, &[Seq::default(), Seq::new(2.0, 2.0, 1.0)]);
+    let mut bytes = Vec::new();
+    write_array(&mut bytes, &column).unwrap();
+    let (de_column, _) = from_slice(&bytes).unwrap();
+    assert!(arrays_equal(&column, &de_column));
+    assert_eq!(de_column.offset(), 0);
+}
+
+#[test]
+fn test_array_from_slice() {
+    let dim = Dim4::new(&[2, 2, 1, 1]);
+    let array = Array::new::<f64>(&[1.0, -2.0, 3.5, 4.0], dim);
+    let mut bytes = Vec::new();
+    write_array(&mut bytes, &array).unwrap();
+
+    let (de_array, len) = from_slice(&bytes).unwrap();
+    assert_eq!(len, bytes.len());
+    assert!(arrays_equal(&array, &de_array));
+
+    assert!(from_slice(&bytes[..bytes.len() - 1]).is_err());
+}
+
+#[test]
+fn test_array_from_slice_concatenated() {
+    let first = Array::new::<i16>(&[1, 2, 3], Dim4::new(&[3, 1, 1, 1]));
+    let second = Array::new::<bool>(&[true, false], Dim4::new(&[1, 2, 1, 1]));
+    let mut bytes = Vec::new();
+    write_array(&mut bytes, &first).unwrap();
+    write_array_with_checksum(&mut bytes, &second, true).unwrap();
+
+    let (de_first, first_len) = from_slice(&bytes).unwrap();
+    assert_eq!(first_len, 40 + 3 * 2);
+    let (de_second, second_len) = from_slice(&bytes[first_len..]).unwrap();
+    assert_eq!(second_len, 40 + 1 + 4);
+    assert_eq!(first_len + second_len, bytes.len());
+    assert!(arrays_equal(&first, &de_first));
+    assert!(arrays_equal(&second, &de_second));
+}
+
+#[test]
+fn test_array_from_slice_corrupt() {
+    let array = Array::new::<f32>(&[1.0, 2.0, 3.0, 4.0], Dim4::new(&[2, 2, 1, 1]));
+    let mut bytes = Vec::new();
+    write_array_with_checksum(&mut bytes, &array, true).unwrap();
+
+    for len in 0..bytes.len() {
+        assert!(from_slice(&bytes[..len]).is_err());
+    }
+
+    // dims whose product overflows, or that claim more data than is present
+    for &dims in &[[u64::MAX, 2, 1, 1], [1 << 62, 1, 1, 1], [2, 2, 2, 1]] {
+        let mut corrupt = bytes.clone();
+        for (i, d) in dims.iter().enumerate() {
+            corrupt[8 + i * 8..16 + i * 8].copy_from_slice(&d.to_le_bytes());
+        }
+        assert!(from_slice(&corrupt).is_err());
+    }
+
+    let mut bad_checksum = bytes.clone();
+    bad_checksum[40] ^= 1;
+    assert!(from_slice(&bad_checksum).is_err());
+}
+
+/// Reads a checked-in fixture from `tests/golden`.
+///
+/// The fixtures are written by hand from the documented formats, not by this
+/// crate, and must never be regenerated: a failing golden test means stored
+/// data would no longer be read back as before.
+fn golden(name: &str) -> Vec<u8> {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(name);
+    std::fs::read(&path).unwrap()
+}
+
+#[test]
+fn test_golden_binary() {
+    let cases = [
+        (
+            "f32_2x3.afsd",
+            Array::new(&[1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0], Dim4::new(&[2, 3, 1, 1])),
+            false,
+        ),
+        (
+            "b8_10_checksum.afsd",
+            Array::new(
+                &[
+                    true, false, true, true, false, false, false, true, false, true,
+                ],
+                Dim4::new(&[10, 1, 1, 1]),
+            ),
+            true,
+        ),
+        (
+            "c64_1x2_checksum.afsd",
+            Array::new(
+                &[Complex::new(1.5f64, -2.0), Complex::new(0.25, 3.0)],
+                Dim4::new(&[1, 2, 1, 1]),
+            ),
+            true,
+        ),
+    ];
+    for &(name, ref expected, checksum) in &cases {
+        let bytes = golden(name);
+        assert!(arrays_equal(
+            &read_array(&mut &bytes[..]).unwrap(),
+            expected
+        ));
+        let (array, len) = from_slice(&bytes).unwrap();
+        assert_eq!(len, bytes.len());
+        assert!(arrays_equal(&array, expected));
+
+        // the writer has to reproduce the stored bytes exactly
+        let mut written = Vec::new();
+        write_array_with_checksum(&mut written, expected, checksum).unwrap();
+        assert_eq!(written, bytes);
+    }
+}
+
+#[cfg(all(feature = "bincode", not(feature = "zerocopy")))]
+#[test]
+fn test_golden_bincode() {
+    let bytes = golden("s16_3.bincode");
+    let expected = Array::new(&[-32768i16, 0, 32767], Dim4::new(&[3, 1, 1, 1]));
+    let array = arrayfire_serde::from_bytes(&bytes).unwrap();
+    assert!(arrays_equal(&array, &expected));
+    assert_eq!(arrayfire_serde::to_bytes(&expected).unwrap(), bytes);
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn test_assert_roundtrips() {
+    let values: Vec<i32> = (0..16).collect();
+    let matrix = Array::new(&values, Dim4::new(&[4, 4, 1, 1]));
+    let arrays = [
+        Array::new(&[1.0f32, f32::NAN, -0.0], Dim4::new(&[3, 1, 1, 1])),
+        Array::new(&[true, false, true], Dim4::new(&[1, 3, 1, 1])),
+        Array::new(
+            &[Complex::new(1.5f64, -2.0), Complex::new(0.25, 3.0)],
+            Dim4::new(&[2, 1, 1, 1]),
+        ),
+        index(&matrix, &[Seq::new(1.0, 2.0, 1.0), Seq::new(0.0, 3.0, 2.0)]),
+        Array::new_empty(Dim4::new(&[0, 2, 1, 1]), DType::U64),
+    ];
+    for array in &arrays {
+        arrayfire_serde::assert_roundtrips(array);
+    }
+}
+
+#[test]
+fn test_from_npy() {
+    // np.arange(6, dtype='<f8').reshape(2, 3)
+    let bytes = golden("f64_2x3.npy");
+    let array = from_npy(&mut &bytes[..]).unwrap();
+    let expected = Array::new(&[0.0f64, 3.0, 1.0, 4.0, 2.0, 5.0], Dim4::new(&[2, 3, 1, 1]));
+    assert!(arrays_equal(&array, &expected));
+
+    // np.asfortranarray([[1, 2], [3, 4]], dtype='>i4')
+    let bytes = golden("i32_2x2_fortran.npy");
+    let array = from_npy(&mut &bytes[..]).unwrap();
+    let expected = Array::new(&[1i32, 3, 2, 4], Dim4::new(&[2, 2, 1, 1]));
+    assert!(arrays_equal(&array, &expected));
+
+    let mut bytes = golden("f64_2x3.npy");
+    bytes.truncate(bytes.len() - 1);
+    match from_npy(&mut &bytes[..]) {
+        Err(Error::Io(_)) => {}
+        other => panic!("expected an io error, got {:?}", other.map(|a| a.dims())),
+    }
+    assert_eq!(
+        from_npy(&mut &b"\x89PNG\r\n\x1a\n"[..])
+            .err()
+            .unwrap()
+            .to_string(),
+        "format error: not a .npy file"
+    );
+}
+
+#[test]
+fn test_to_npy() {
+    let arrays = [
+        Array::new(&[0.0f64, 3.0, 1.0, 4.0, 2.0, 5.0], Dim4::new(&[2, 3, 1, 1])),
+        Array::new(&[1u8, 2, 3, 4, 5, 6, 7, 8], Dim4::new(&[2, 1, 2, 2])),
+        Array::new(&[true, false, true], Dim4::new(&[3, 1, 1, 1])),
+        Array::new(
+            &[Complex::new(1.5f32, -2.0), Complex::new(0.25, 3.0)],
+            Dim4::new(&[1, 2, 1, 1]),
+        ),
+        Array::new_empty(Dim4::new(&[0, 4, 1, 1]), DType::S64),
+    ];
+    for array in &arrays {
+        let mut bytes = Vec::new();
+        to_npy(&mut bytes, array).unwrap();
+        let de_array = from_npy(&mut &bytes[..]).unwrap();
+        assert!(arrays_equal(&de_array, array));
+    }
+
+    let mut bytes = Vec::new();
+    to_npy(&mut bytes, &arrays[0]).unwrap();
+    let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+    assert_eq!(&bytes[..8], b"\x93NUMPY\x01\x00");
+    assert_eq!((10 + header_len) % 64, 0);
+    assert_eq!(
+        std::str::from_utf8(&bytes[10..10 + header_len])
+            .unwrap()
+            .trim_end(),
+        "{'descr': '<f8', 'fortran_order': True, 'shape': (2, 3), }"
+    );
+    assert_eq!(bytes.len(), 10 + header_len + 6 * 8);
+}
+
+#[test]
+fn test_array_window() {
+    use std::io::Cursor;
+
+    let values: Vec<i32> = (0..16).collect();
+    let array = Array::new::<i32>(&values, Dim4::new(&[4, 4, 1, 1]));
+    let mut bytes = Vec::new();
+    write_array(&mut bytes, &array).unwrap();
+
+    // rows 1..=2 of columns 2..=3
+    let block = deserialize_window(
+        Dim4::new(&[1, 2, 0, 0]),
+        Dim4::new(&[2, 2, 1, 1]),
+        &mut Cursor::new(&bytes),
+    )
+    .unwrap();
+    let expected = Array::new::<i32>(&[9, 10, 13, 14], Dim4::new(&[2, 2, 1, 1]));
+    assert!(arrays_equal(&block, &expected));
+
+    // bit packed B8 runs that start mid-byte
+    let bits: Vec<bool> = (0..16).map(|i| i % 3 == 0).collect();
+    let mut bytes = Vec::new();
+    write_array(&mut bytes, &Array::new(&bits, Dim4::new(&[4, 4, 1, 1]))).unwrap();
+    let block = deserialize_window(
+        Dim4::new(&[1, 2, 0, 0]),
+        Dim4::new(&[2, 2, 1, 1]),
+        &mut Cursor::new(&bytes),
+    )
+    .unwrap();
+    let expected = Array::new(
+        &[bits[9], bits[10], bits[13], bits[14]],
+        Dim4::new(&[2, 2, 1, 1]),
+    );
+    assert!(arrays_equal(&block, &expected));
+
+    assert!(deserialize_window(
+        Dim4::new(&[3, 0, 0, 0]),
+        Dim4::new(&[2, 1, 1, 1]),
+        &mut Cursor::new(&bytes),
+    )
+    .is_err());
+}
+
+#[test]
+fn test_array_checksum() {
+    let dim = Dim4::new(&[4, 1, 1, 1]);
+    let array = Array::new::<u32>(&[1, 2, 3, 4], dim);
+
+    let mut bytes = Vec::new();
+    write_array_with_checksum(&mut bytes, &array, true).unwrap();
+    assert_eq!(bytes.len(), 40 + 4 * 4 + 4);
+    let de_array = read_array(&mut bytes.as_slice()).unwrap();
+    assert_eq!(de_array.dims(), dim);
+
+    // flip a byte of the data section
+    bytes[41] ^= 0xff;
+    match read_array(&mut bytes.as_slice()) {
+        Err(Error::ChecksumMismatch { expected, found }) => assert_ne!(expected, found),
+        Err(err) => panic!("unexpected error {}", err),
+        Ok(_) => panic!("corruption was not detected"),
+    }
+}
+
+#[test]
+fn test_array_deserialize_as() {
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("f64"),
+        Token::Tuple { len: 4 },
+        Token::U64(3),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(3) },
+        Token::F64(0.1),
+        Token::F64(-2.5),
+        Token::F64(1e10),
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    let array = deserialize_as(DType::F32, (&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(array.get_type(), DType::F32);
+    assert_eq!(array.dims(), Dim4::new(&[3, 1, 1, 1]));
+
+    let mut array_vec = vec![0f32; array.elements()];
+    array.host(array_vec.as_mut_slice());
+    for (&value, &expected) in array_vec.iter().zip(&[0.1f32, -2.5, 1e10]) {
+        assert!((value - expected).abs() <= expected.abs() * 1e-6);
+    }
+}
+
+fn deserialize_promote_tokens(dtype: u8, data: &[Token], target: DType) -> Result<Array, String> {
+    let mut tokens = vec![
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::U8(dtype),
+        Token::Tuple { len: 4 },
+        Token::U64(data.len() as u64),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq {
+            len: Some(data.len()),
+        },
+    ];
+    tokens.extend_from_slice(data);
+    tokens.extend_from_slice(&[Token::SeqEnd, Token::TupleEnd]);
+    let mut de = Deserializer::new(&tokens);
+    deserialize_promote(target, (&mut de).readable()).map_err(|e| e.to_string())
+}
+
+#[test]
+fn test_array_deserialize_promote() {
+    let dims = Dim4::new(&[3, 1, 1, 1]);
+
+    let array = deserialize_promote_tokens(
+        10,
+        &[Token::I16(-32768), Token::I16(32767), Token::I16(12345)],
+        DType::F32,
+    )
+    .unwrap();
+    assert!(arrays_equal(
+        &array,
+        &Array::new(&[-32768f32, 32767.0, 12345.0], dims)
+    ));
+
+    let array = deserialize_promote_tokens(
+        7,
+        &[Token::U8(0), Token::U8(128), Token::U8(255)],
+        DType::S32,
+    )
+    .unwrap();
+    assert!(arrays_equal(&array, &Array::new(&[0i32, 128, 255], dims)));
+
+    let array = deserialize_promote_tokens(
+        0,
+        &[Token::F32(0.5), Token::F32(-2.25), Token::F32(1e10)],
+        DType::F64,
+    )
+    .unwrap();
+    assert!(arrays_equal(
+        &array,
+        &Array::new(&[0.5f64, -2.25, 1e10], dims)
+    ));
+
+    let array = deserialize_promote_tokens(
+        6,
+        &[Token::U32(0), Token::U32(7), Token::U32(u32::MAX)],
+        DType::S64,
+    )
+    .unwrap();
+    assert!(arrays_equal(
+        &array,
+        &Array::new(&[0i64, 7, u32::MAX as i64], dims)
+    ));
+
+    for &(dtype, target) in &[(0, DType::F32), (2, DType::C64), (5, DType::C32)] {
+        let array =
+            deserialize_promote_tokens(dtype, &[Token::U8(1), Token::U8(2), Token::U8(3)], target)
+                .unwrap();
+        assert_eq!(array.get_type(), target);
+    }
+}
+
+#[test]
+fn test_array_deserialize_promote_narrowing() {
+    let cases = [
+        (2, DType::F64, DType::F32),
+        (5, DType::S32, DType::S16),
+        (10, DType::S16, DType::U16),
+        (8, DType::S64, DType::U64),
+        (2, DType::F64, DType::C32),
+        (0, DType::F32, DType::S32),
+    ];
+    for &(tag, found, expected) in &cases {
+        let error = deserialize_promote_tokens(tag, &[Token::U8(1)], expected)
+            .err()
+            .unwrap();
+        assert_eq!(error, Error::DTypeMismatch { expected, found }.to_string());
+    }
+}
+
+#[test]
+fn test_array_chunked() {
+    let values: Vec<f32> = (0..10).map(|i| i as f32 * 0.5).collect();
+    let real = Array::new(&values, Dim4::new(&[5, 2, 1, 1]));
+    let complex = Array::new(
+        &[Complex::new(1.0f64, -1.0), Complex::new(2.0, 0.5)],
+        Dim4::new(&[1, 2, 1, 1]),
+    );
+    let bits = Array::new(&[true, false, true], Dim4::new(&[3, 1, 1, 1]));
+    let empty = Array::new_empty(Dim4::new(&[0, 1, 1, 1]), DType::U32);
+
+    for array in &[real, complex, bits, empty] {
+        let expected = serde_json::to_string(&Ser::new(array)).unwrap();
+        for &chunk in &[0, 1, 2, 3, 7, 100] {
+            let json = serde_json::to_string(&ChunkedArray(array, chunk)).unwrap();
+            assert_eq!(json, expected);
+        }
+    }
+}
+
+#[cfg(all(feature = "zerocopy", feature = "bincode"))]
+#[test]
+fn test_array_chunked_zerocopy() {
+    let array = Array::new(&[1.0f32, 2.0, 3.0], Dim4::new(&[3, 1, 1, 1]));
+    let bytes = bincode::serialize(&ChunkedArray(&array, 2)).unwrap();
+    assert_eq!(bytes, arrayfire_serde::to_bytes(&array).unwrap());
+    let de_array = bincode::deserialize::<Serde<Array>>(&bytes)
+        .unwrap()
+        .into_inner();
+    assert!(arrays_equal(&array, &de_array));
+}
+
+#[test]
+fn test_view_dims() {
+    let dims_tokens = |dims: [u64; 4]| {
+        vec![
+            Token::Tuple { len: 4 },
+            Token::U64(dims[0]),
+            Token::U64(dims[1]),
+            Token::U64(dims[2]),
+            Token::U64(dims[3]),
+            Token::TupleEnd,
+        ]
+    };
+    let tokens = |parent, view, offset| {
+        let mut tokens = vec![Token::Struct {
+            name: "ViewDims",
+            len: 3,
+        }];
+        tokens.push(Token::Str("parent"));
+        tokens.extend(dims_tokens(parent));
+        tokens.push(Token::Str("view"));
+        tokens.extend(dims_tokens(view));
+        tokens.push(Token::Str("offset"));
+        tokens.extend(dims_tokens(offset));
+        tokens.push(Token::StructEnd);
+        tokens
+    };
+
+    let view_dims = ViewDims {
+        parent: Dim4::new(&[8, 6, 3, 1]),
+        view: Dim4::new(&[4, 2, 3, 1]),
+        offset: Dim4::new(&[4, 1, 0, 0]),
+    };
+    assert_tokens(
+        &view_dims,
+        &tokens([8, 6, 3, 1], [4, 2, 3, 1], [4, 1, 0, 0]),
+    );
+
+    let outside = tokens([8, 6, 3, 1], [4, 2, 3, 1], [5, 1, 0, 0]);
+    let mut de = Deserializer::new(&outside);
+    assert_eq!(
+        ViewDims::deserialize(&mut de).err().unwrap().to_string(),
+        "view of [4, 2, 3, 1] at [5, 1, 0, 0] exceeds parent dims [8, 6, 3, 1]"
+    );
+}
+
+#[test]
+fn test_coo_sparse() {
+    let dim = Dim4::new(&[3, 1, 1, 1]);
+    let sparse = CooSparse {
+        rows: Array::new(&[0i32, 2, 4], dim),
+        cols: Array::new(&[1i32, 1, 3], dim),
+        values: Array::new(&[0.5f32, -1.0, 2.0], dim),
+        shape: Dim4::new(&[5, 4, 1, 1]),
+    };
+    let json = serde_json::to_string(&sparse).unwrap();
+    let de_sparse: CooSparse = serde_json::from_str(&json).unwrap();
+    assert!(arrays_equal(&sparse.rows, &de_sparse.rows));
+    assert!(arrays_equal(&sparse.cols, &de_sparse.cols));
+    assert!(arrays_equal(&sparse.values, &de_sparse.values));
+    assert_eq!(sparse.shape, de_sparse.shape);
+
+    let float_rows = CooSparse {
+        rows: Array::new(&[0.0f32, 2.0, 4.0], dim),
+        ..de_sparse
+    };
+    assert_eq!(
+        serde_json::to_string(&float_rows)
+            .err()
+            .unwrap()
+            .to_string(),
+        "format error: sparse rows must have an integer dtype, found F32"
+    );
+
+    // lengths are checked when reading too
+    let json = serde_json::json!({
+        "rows": serde_json::to_value(Ser::new(&sparse.rows)).unwrap(),
+        "cols": serde_json::to_value(Ser::new(&Array::new(&[1i32, 1], Dim4::new(&[2, 1, 1, 1])))).unwrap(),
+        "values": serde_json::to_value(Ser::new(&sparse.values)).unwrap(),
+        "shape": serde_json::to_value(Ser::new(&sparse.shape)).unwrap(),
+    });
+    assert_eq!(
+        serde_json::from_value::<CooSparse>(json)
+            .err()
+            .unwrap()
+            .to_string(),
+        "format error: sparse rows, cols and values differ in length: 3, 2 and 3"
+    );
+}
+
+#[test]
+fn test_array_header() {
+    let dim = Dim4::new(&[2, 2, 1, 1]);
+    let array = Array::new::<f64>(&[1.0, 2.0, 3.0, 4.0], dim);
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("f64"),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(4) },
+        Token::F64(1.0),
+        Token::F64(2.0),
+        Token::F64(3.0),
+        Token::F64(4.0),
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&Ser::new(&array).readable(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let (dtype, dims) = deserialize_header((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    let de_array = deserialize::<Array, _>((&mut Deserializer::new(&tokens)).readable()).unwrap();
+    assert_eq!(dtype, de_array.get_type());
+    assert_eq!(dims, de_array.dims());
+
+    let mut bytes = Vec::new();
+    write_array(&mut bytes, &array).unwrap();
+    let mut reader = bytes.as_slice();
+    assert_eq!(read_array_header(&mut reader).unwrap(), (DType::F64, dim));
+    assert_eq!(reader.len(), 4 * 8);
+}
+
+struct Windows(Vec<Dim4>);
+
+impl Serialize for Windows {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        dim4_vec::serialize(&self.0, serializer)
+    }
+}
+
+#[test]
+fn test_dim4_vec() {
+    let windows = vec![
+        Dim4::new(&[1, 2, 3, 4]),
+        Dim4::new(&[5, 1, 1, 1]),
+        Dim4::new(&[3, 3, 1, 1]),
+    ];
+    let mut tokens = vec![Token::Seq { len: Some(3) }];
+    for dim in &windows {
+        tokens.push(Token::Tuple { len: 4 });
+        tokens.extend(dim.get().iter().map(|&d| Token::U64(d)));
+        tokens.push(Token::TupleEnd);
+    }
+    tokens.push(Token::SeqEnd);
+    assert_ser_tokens(&Windows(windows.clone()), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let de_windows = dim4_vec::deserialize(&mut de).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(windows, de_windows);
+}
+
+struct Columns(Vec<DType>);
+
+impl Serialize for Columns {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        dtype_vec::serialize(&self.0, serializer)
+    }
+}
+
+#[test]
+fn test_dtype_vec() {
+    let columns = vec![DType::F32, DType::U8, DType::C64, DType::S16];
+    let tokens = [
+        Token::Seq { len: Some(4) },
+        Token::Str("f32"),
+        Token::Str("u8"),
+        Token::Str("c64"),
+        Token::Str("s16"),
+        Token::SeqEnd,
+    ];
+    assert_ser_tokens(&Columns(columns.clone()).readable(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let de_columns = dtype_vec::deserialize((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(columns, de_columns);
+}
+
+/// Owned array compared by dtype, dims and host data read as `T`.
+struct Typed<T>(Array, PhantomData<T>);
+
+impl<T: HasAfEnum + Clone + Default> Typed<T> {
+    fn host(&self) -> Vec<T> {
+        let mut data = vec![T::default(); self.0.elements()];
+        self.0.host(data.as_mut_slice());
+        data
+    }
+}
+
+impl<T: HasAfEnum + Clone + Default + PartialEq> PartialEq for Typed<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.get_type() == other.0.get_type()
+            && self.0.dims() == other.0.dims()
+            && self.host() == other.host()
+    }
+}
+
+impl<T: HasAfEnum + Clone + Default + fmt::Debug> fmt::Debug for Typed<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:?} {:?} {:?}",
+            self.0.get_type(),
+            self.0.dims(),
+            self.host()
+        )
+    }
+}
+
+impl<T> Serialize for Typed<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Ser::new(&self.0).serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Typed<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize(deserializer).map(|array| Typed(array, PhantomData))
+    }
+}
+
+/// Asserts that a column vector of `values` serializes to the expected tokens
+/// and that those tokens deserialize back to an equal array.
+fn assert_array_tokens<T>(values: &[T], dtype: &'static str, element: fn(&T) -> Vec<Token>)
+where
+    T: HasAfEnum + Clone + Default + PartialEq + fmt::Debug,
+{
+    let len = values.len() as u64;
+    let array = Typed::<T>(Array::new(values, Dim4::new(&[len, 1, 1, 1])), PhantomData);
+    let mut tokens = vec![
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str(dtype),
+        Token::Tuple { len: 4 },
+        Token::U64(len),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq {
+            len: Some(values.len()),
+        },
+    ];
+    for value in values {
+        tokens.extend(element(value));
+    }
+    tokens.push(Token::SeqEnd);
+    tokens.push(Token::TupleEnd);
+
+    assert_ser_tokens(&(&array).readable(), &tokens);
+    assert_de_tokens(&array.readable(), &tokens);
+}
+
+#[test]
+fn test_array_tokens_all_dtypes() {
+    assert_array_tokens(&[1.5f32, -2.0], "f32", |&v| vec![Token::F32(v)]);
+    assert_array_tokens(
+        &[Complex {
+            re: 1.0f32,
+            im: -1.0,
+        }],
+        "c32",
+        |c| {
+            vec![
+                Token::Tuple { len: 2 },
+                Token::F32(c.re),
+                Token::F32(c.im),
+                Token::TupleEnd,
+            ]
+        },
+    );
+    assert_array_tokens(&[0.25f64, 8.0], "f64", |&v| vec![Token::F64(v)]);
+    assert_array_tokens(
+        &[Complex {
+            re: -3.0f64,
+            im: 0.5,
+        }],
+        "c64",
+        |c| {
+            vec![
+                Token::Tuple { len: 2 },
+                Token::F64(c.re),
+                Token::F64(c.im),
+                Token::TupleEnd,
+            ]
+        },
+    );
+    assert_array_tokens(&[true, false, true], "b8", |&v| vec![Token::Bool(v)]);
+    assert_array_tokens(&[-7i32, 7], "s32", |&v| vec![Token::I32(v)]);
+    assert_array_tokens(&[7u32, 0], "u32", |&v| vec![Token::U32(v)]);
+    assert_array_tokens(&[255u8, 0], "u8", |&v| vec![Token::U8(v)]);
+    assert_array_tokens(&[i64::MIN, 1], "s64", |&v| vec![Token::I64(v)]);
+    assert_array_tokens(&[u64::MAX, 1], "u64", |&v| vec![Token::U64(v)]);
+    // S16 and U16 are left out: `Array::get_type` in arrayfire 3.5 panics on them
+}
+
+#[test]
+fn test_array_scalar_all_dtypes() {
+    // a single element has dims [1, 1, 1, 1] and is written as a sequence of length one
+    assert_array_tokens(&[-1.5f32], "f32", |&v| vec![Token::F32(v)]);
+    assert_array_tokens(
+        &[Complex {
+            re: 0.5f32,
+            im: 2.0,
+        }],
+        "c32",
+        |c| {
+            vec![
+                Token::Tuple { len: 2 },
+                Token::F32(c.re),
+                Token::F32(c.im),
+                Token::TupleEnd,
+            ]
+        },
+    );
+    assert_array_tokens(&[1e300f64], "f64", |&v| vec![Token::F64(v)]);
+    assert_array_tokens(
+        &[Complex {
+            re: 0.0f64,
+            im: -0.25,
+        }],
+        "c64",
+        |c| {
+            vec![
+                Token::Tuple { len: 2 },
+                Token::F64(c.re),
+                Token::F64(c.im),
+                Token::TupleEnd,
+            ]
+        },
+    );
+    assert_array_tokens(&[false], "b8", |&v| vec![Token::Bool(v)]);
+    assert_array_tokens(&[i32::MIN], "s32", |&v| vec![Token::I32(v)]);
+    assert_array_tokens(&[u32::MAX], "u32", |&v| vec![Token::U32(v)]);
+    assert_array_tokens(&[0u8], "u8", |&v| vec![Token::U8(v)]);
+    assert_array_tokens(&[-1i64], "s64", |&v| vec![Token::I64(v)]);
+    assert_array_tokens(&[1u64 << 63], "u64", |&v| vec![Token::U64(v)]);
+
+    // S16 and U16 are compared with `arrays_equal`, which does not need `Array::get_type`
+    let scalar_tokens = |dtype: &'static str, element: Token| {
+        vec![
+            Token::Tuple { len: 4 },
+            Token::U16(1),
+            Token::Str(dtype),
+            Token::Tuple { len: 4 },
+            Token::U64(1),
+            Token::U64(1),
+            Token::U64(1),
+            Token::U64(1),
+            Token::TupleEnd,
+            Token::Seq { len: Some(1) },
+            element,
+            Token::SeqEnd,
+            Token::TupleEnd,
+        ]
+    };
+    let scalar = Dim4::new(&[1, 1, 1, 1]);
+    for (array, tokens) in &[
+        (
+            Array::new::<i16>(&[i16::MIN], scalar),
+            scalar_tokens("s16", Token::I16(i16::MIN)),
+        ),
+        (
+            Array::new::<u16>(&[u16::MAX], scalar),
+            scalar_tokens("u16", Token::U16(u16::MAX)),
+        ),
+    ] {
+        assert_ser_tokens(&Ser::new(array).readable(), tokens);
+        let mut de = Deserializer::new(tokens);
+        let de_array = deserialize::<Array, _>((&mut de).readable()).unwrap();
+        assert_eq!(de.next_token_opt(), None);
+        assert!(arrays_equal(array, &de_array));
+    }
+}
+
+#[cfg(all(feature = "zerocopy", target_endian = "little"))]
+#[test]
+fn test_array_zerocopy() {
+    let dim = Dim4::new(&[2, 1, 1, 1]);
+    let values: [f32; 2] = [1.0, 2.0];
+    let array = Array::new::<f32>(&values, dim);
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::U8(0),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Bytes(&[0x00, 0x00, 0x80, 0x3f, 0x00, 0x00, 0x00, 0x40]),
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&Ser::new(&array).compact(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let de_array = deserialize::<Array, _>((&mut de).compact()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(de_array.get_type(), DType::F32);
+    assert_eq!(de_array.dims(), dim);
+
+    let mut de_array_vec = vec![0f32; de_array.elements()];
+    de_array.host(de_array_vec.as_mut_slice());
+    assert_eq!(&values[..], &de_array_vec[..]);
+
+    #[cfg(feature = "bincode")]
+    {
+        let arrays = [
+            Array::new(&[-3i16, 0, 7], Dim4::new(&[3, 1, 1, 1])),
+            Array::new(&[u64::MAX, 1], Dim4::new(&[1, 2, 1, 1])),
+            Array::new(&[true, false, true], Dim4::new(&[3, 1, 1, 1])),
+            Array::new(
+                &[Complex::new(1.5f64, -2.0), Complex::new(0.25, 3.0)],
+                Dim4::new(&[2, 1, 1, 1]),
+            ),
+        ];
+        for array in &arrays {
+            let bytes = arrayfire_serde::to_bytes(array).unwrap();
+            let de_array = arrayfire_serde::from_bytes(&bytes).unwrap();
+            assert!(arrays_equal(array, &de_array));
+        }
+    }
+}
+
+#[test]
+fn test_array_file_chunked() {
+    // larger than the 4 MB chunk used by `write_array`
+    let elements = 1_500_000;
+    let values: Vec<f32> = (0..elements).map(|i| i as f32).collect();
+    let dim = Dim4::new(&[1000, 1500, 1, 1]);
+    let array = Array::new::<f32>(&values, dim);
+
+    let mut bytes = Vec::new();
+    write_array_with_checksum(&mut bytes, &array, true).unwrap();
+    assert_eq!(bytes.len(), 40 + elements * 4 + 4);
+
+    let de_array = read_array(&mut bytes.as_slice()).unwrap();
+    assert_eq!(de_array.dims(), dim);
+    let mut de_array_vec = vec![0f32; de_array.elements()];
+    de_array.host(de_array_vec.as_mut_slice());
+    assert_eq!(values, de_array_vec);
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_array_compressed() {
+    let values: Vec<f32> = (0..4096).map(|i| (i % 16) as f32).collect();
+    let dim = Dim4::new(&[64, 64, 1, 1]);
+    let array = Array::new(&values, dim);
+
+    let mut bytes = Vec::new();
+    arrayfire_serde::serialize_compressed(&mut bytes, &array, 3).unwrap();
+    assert_eq!(&bytes[..4], b"AFSZ");
+    assert_eq!(bytes[4..12], ((40 + 4096 * 4) as u64).to_le_bytes());
+    assert!(bytes.len() < 4096 * 4);
+
+    let de_array = arrayfire_serde::deserialize_compressed(&mut bytes.as_slice()).unwrap();
+    assert_eq!(de_array.dims(), dim);
+    assert!(arrays_equal(&array, &de_array));
+
+    let truncated = &bytes[..bytes.len() / 2];
+    match arrayfire_serde::deserialize_compressed(&mut &truncated[..]) {
+        Err(Error::Io(ref err)) if err.kind() == std::io::ErrorKind::UnexpectedEof => {}
+        Err(err) => panic!("unexpected error {}", err),
+        Ok(_) => panic!("truncated stream was accepted"),
+    }
+}
+
+#[test]
+fn test_array_single_axis_dims() {
+    let values: [f64; 3] = [1.0, 2.0, 3.0];
+    for axis in 0..4 {
+        let mut dims = [1u64; 4];
+        dims[axis] = 3;
+        let dim = Dim4::new(&dims);
+        let array = Array::new::<f64>(&values, dim);
+
+        let mut tokens = vec![
+            Token::Tuple { len: 4 },
+            Token::U16(1),
+            Token::Str("f64"),
+            Token::Tuple { len: 4 },
+        ];
+        tokens.extend(dims.iter().map(|&d| Token::U64(d)));
+        tokens.extend(vec![
+            Token::TupleEnd,
+            Token::Seq { len: Some(3) },
+            Token::F64(1.0),
+            Token::F64(2.0),
+            Token::F64(3.0),
+            Token::SeqEnd,
+            Token::TupleEnd,
+        ]);
+        assert_ser_tokens(&Ser::new(&array).readable(), &tokens);
+
+        let mut de = Deserializer::new(&tokens);
+        let de_array = deserialize::<Array, _>((&mut de).readable()).unwrap();
+        assert_eq!(de_array.dims(), dim);
+
+        let mut packed = vec![
+            Token::Tuple { len: 5 },
+            Token::U16(1),
+            Token::U8(2),
+            Token::Tuple { len: 4 },
+        ];
+        packed.extend(dims.iter().map(|&d| Token::U64(d)));
+        packed.extend(vec![
+            Token::TupleEnd,
+            Token::U8(if cfg!(target_endian = "big") { 1 } else { 0 }),
+        ]);
+        packed.push(Token::Bytes(if cfg!(target_endian = "big") {
+            &[
+                0x3f, 0xf0, 0, 0, 0, 0, 0, 0, 0x40, 0, 0, 0, 0, 0, 0, 0, 0x40, 0x08, 0, 0, 0, 0, 0,
+                0,
+            ]
+        } else {
+            &[
+                0, 0, 0, 0, 0, 0, 0xf0, 0x3f, 0, 0, 0, 0, 0, 0, 0, 0x40, 0, 0, 0, 0, 0, 0, 0x08,
+                0x40,
+            ]
+        }));
+        packed.push(Token::TupleEnd);
+        assert_ser_tokens(&Packed(&array).compact(), &packed);
+        let mut de = Deserializer::new(&packed);
+        assert_eq!(deserialize_packed((&mut de).compact()).unwrap().dims(), dim);
+
+        let mut bytes = Vec::new();
+        write_array(&mut bytes, &array).unwrap();
+        assert_eq!(read_array(&mut bytes.as_slice()).unwrap().dims(), dim);
+    }
+}
+
+struct JsonSafe<'a>(&'a Array);
+
+impl<'a> Serialize for JsonSafe<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_json_safe(self.0, serializer)
+    }
+}
+
+#[test]
+fn test_array_json_safe() {
+    let dim = Dim4::new(&[4, 1, 1, 1]);
+    let values = [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1.5];
+    let array = Array::new::<f64>(&values, dim);
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("f64"),
+        Token::Tuple { len: 4 },
+        Token::U64(4),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(4) },
+        Token::Str("NaN"),
+        Token::Str("Infinity"),
+        Token::Str("-Infinity"),
+        Token::F64(1.5),
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&JsonSafe(&array).readable(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let de_array = deserialize_json_safe((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(de_array.get_type(), DType::F64);
+
+    let mut de_array_vec = vec![0f64; de_array.elements()];
+    de_array.host(de_array_vec.as_mut_slice());
+    assert!(de_array_vec[0].is_nan());
+    assert_eq!(de_array_vec[1], f64::INFINITY);
+    assert_eq!(de_array_vec[2], f64::NEG_INFINITY);
+    assert_eq!(de_array_vec[3], 1.5);
+}
+
+struct Rounded<'a>(u32, &'a Array);
+
+impl<'a> Serialize for Rounded<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_rounded(self.0, self.1, serializer)
+    }
+}
+
+#[test]
+fn test_array_rounded() {
+    let dim = Dim4::new(&[4, 1, 1, 1]);
+    let values = [1.23456f64, -0.005001, 2.0, f64::NAN];
+    let array = Array::new::<f64>(&values, dim);
+    assert_eq!(
+        serde_json::to_string(&Rounded(2, &array)).unwrap(),
+        "[1,\"f64\",[4,1,1,1],[1.23,-0.01,2.0,null]]"
+    );
+    assert_eq!(
+        serde_json::to_string(&Rounded(0, &array)).unwrap(),
+        "[1,\"f64\",[4,1,1,1],[1.0,-0.0,2.0,null]]"
+    );
+
+    let values = [Complex::new(0.123456f32, 9.87654)];
+    let array = Array::new(&values, Dim4::new(&[1, 1, 1, 1]));
+    assert_eq!(
+        serde_json::to_string(&Rounded(3, &array)).unwrap(),
+        "[1,\"c32\",[1,1,1,1],[[0.123,9.877]]]"
+    );
+
+    // other dtypes are unaffected
+    let array = Array::new(&[123456789i32], Dim4::new(&[1, 1, 1, 1]));
+    assert_eq!(
+        serde_json::to_string(&Rounded(0, &array)).unwrap(),
+        serde_json::to_string(&Ser::new(&array)).unwrap()
+    );
+}
+
+#[cfg(feature = "bincode")]
+#[test]
+fn test_array_rounded_bincode() {
+    let values = [1.23456f64, -0.005001, 2.0];
+    let array = Array::new::<f64>(&values, Dim4::new(&[3, 1, 1, 1]));
+    let bytes = bincode::serialize(&Rounded(2, &array)).unwrap();
+    assert_eq!(bytes, arrayfire_serde::to_bytes(&array).unwrap());
+    let de_array = arrayfire_serde::from_bytes(&bytes).unwrap();
+    let mut de_values = [0f64; 3];
+    de_array.host(&mut de_values);
+    assert_eq!(de_values, values);
+}
+
+struct CheckedFinite<'a>(&'a Array);
+
+impl<'a> Serialize for CheckedFinite<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_checked_finite(self.0, serializer)
+    }
+}
+
+#[test]
+fn test_array_checked_finite() {
+    let dim = Dim4::new(&[3, 1, 1, 1]);
+    let nan = Array::new::<f32>(&[1.0, f32::NAN, 2.0], dim);
+    assert_ser_tokens_error(
+        &CheckedFinite(&nan).readable(),
+        &[],
+        &Error::NonFinite { dtype: DType::F32 }.to_string(),
+    );
+    let inf = Array::new(
+        &[
+            Complex::new(0.0f64, 1.0),
+            Complex::new(f64::NEG_INFINITY, 0.0),
+        ],
+        Dim4::new(&[2, 1, 1, 1]),
+    );
+    assert_ser_tokens_error(
+        &CheckedFinite(&inf).readable(),
+        &[],
+        "C64 array holds NaN or infinite values",
+    );
+
+    // finite floats and other dtypes serialize like `serialize`
+    let finite = Array::new::<f64>(&[1.0, -0.5, 1e300], dim);
+    let ints = Array::new::<i32>(&[i32::MIN, 0, i32::MAX], dim);
+    for array in &[finite, ints] {
+        assert_eq!(
+            serde_json::to_string(&CheckedFinite(array)).unwrap(),
+            serde_json::to_string(&Ser::new(array)).unwrap()
+        );
+    }
+}
+
+struct JsSafe<'a>(&'a Array);
+
+impl<'a> Serialize for JsSafe<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_js_safe(self.0, serializer)
+    }
+}
+
+#[test]
+fn test_array_js_safe() {
+    let tokens = |dtype: &'static str, elements: Vec<Token>| {
+        let mut tokens = vec![
+            Token::Tuple { len: 4 },
+            Token::U16(1),
+            Token::Str(dtype),
+            Token::Tuple { len: 4 },
+            Token::U64(elements.len() as u64),
+            Token::U64(1),
+            Token::U64(1),
+            Token::U64(1),
+            Token::TupleEnd,
+            Token::Seq {
+                len: Some(elements.len()),
+            },
+        ];
+        tokens.extend(elements);
+        tokens.extend(vec![Token::SeqEnd, Token::TupleEnd]);
+        tokens
+    };
+    let load = |tokens: &[Token]| {
+        let mut de = Deserializer::new(tokens);
+        let array = deserialize_js_safe((&mut de).readable()).unwrap();
+        assert_eq!(de.next_token_opt(), None);
+        array
+    };
+
+    // values above 2^53 round-trip exactly as strings
+    let big = (1u64 << 53) + 1;
+    let unsigned = Array::new::<u64>(&[big, u64::MAX], Dim4::new(&[2, 1, 1, 1]));
+    let unsigned_tokens = tokens(
+        "u64",
+        vec![
+            Token::Str("9007199254740993"),
+            Token::Str("18446744073709551615"),
+        ],
+    );
+    assert_ser_tokens(&JsSafe(&unsigned).readable(), &unsigned_tokens);
+    assert!(arrays_equal(&unsigned, &load(&unsigned_tokens)));
+
+    let signed = Array::new::<i64>(&[-(big as i64), i64::MIN], Dim4::new(&[2, 1, 1, 1]));
+    let signed_tokens = tokens(
+        "s64",
+        vec![
+            Token::Str("-9007199254740993"),
+            Token::Str("-9223372036854775808"),
+        ],
+    );
+    assert_ser_tokens(&JsSafe(&signed).readable(), &signed_tokens);
+    assert!(arrays_equal(&signed, &load(&signed_tokens)));
+
+    // plain numbers are accepted as well, and floats stay numeric
+    let numbers = load(&tokens("u64", vec![Token::U64(big)]));
+    assert!(arrays_equal(
+        &numbers,
+        &Array::new::<u64>(&[big], Dim4::new(&[1, 1, 1, 1]))
+    ));
+    let floats = Array::new::<f64>(&[0.5], Dim4::new(&[1, 1, 1, 1]));
+    assert_ser_tokens(
+        &JsSafe(&floats).readable(),
+        &tokens("f64", vec![Token::F64(0.5)]),
+    );
+
+    let negative_tokens = tokens("u64", vec![Token::Str("-1")]);
+    let mut de = Deserializer::new(&negative_tokens);
+    assert!(deserialize_js_safe((&mut de).readable()).is_err());
+
+    // the string transport is not recorded, so compact formats are rejected
+    assert_ser_tokens_error(
+        &JsSafe(&unsigned).compact(),
+        &[],
+        &Error::Format(
+            "serialize_js_safe requires a human readable format, as the string transport of \
+             64-bit integers is not recorded"
+                .to_string(),
+        )
+        .to_string(),
+    );
+    let mut de = Deserializer::new(&unsigned_tokens);
+    assert_eq!(
+        deserialize_js_safe((&mut de).compact())
+            .err()
+            .unwrap()
+            .to_string(),
+        Error::Format(
+            "deserialize_js_safe requires a human readable format, as the string transport of \
+             64-bit integers is not recorded"
+                .to_string(),
+        )
+        .to_string()
+    );
+}
+
+#[test]
+fn test_array_json_safe_invalid_sentinel() {
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("f32"),
+        Token::Tuple { len: 4 },
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(1) },
+        Token::Str("nan"),
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    assert!(deserialize_json_safe((&mut de).readable()).is_err());
+}
+
+struct Ordered<'a>(Order, &'a Array);
+
+impl<'a> Serialize for Ordered<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_ordered(self.0, self.1, serializer)
+    }
+}
+
+#[test]
+fn test_array_ordered() {
+    // [[1, 3, 5],
+    //  [2, 4, 6]]
+    let dim = Dim4::new(&[2, 3, 1, 1]);
+    let values: [f32; 6] = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+    let array = Array::new::<f32>(&values, dim);
+
+    for &(order, tag, data) in &[
+        (Order::ColumnMajor, 0, [1.0, 2.0, 3.0, 4.0, 5.0, 6.0]),
+        (Order::RowMajor, 1, [1.0, 3.0, 5.0, 2.0, 4.0, 6.0]),
+    ] {
+        let mut tokens = vec![
+            Token::Tuple { len: 5 },
+            Token::U16(1),
+            Token::Str("f32"),
+            Token::Tuple { len: 4 },
+            Token::U64(2),
+            Token::U64(3),
+            Token::U64(1),
+            Token::U64(1),
+            Token::TupleEnd,
+            Token::U8(tag),
+            Token::Seq { len: Some(6) },
+        ];
+        tokens.extend(data.iter().map(|&v| Token::F32(v)));
+        tokens.push(Token::SeqEnd);
+        tokens.push(Token::TupleEnd);
+        assert_ser_tokens(&Ordered(order, &array).readable(), &tokens);
+
+        let mut de = Deserializer::new(&tokens);
+        let de_array = deserialize_ordered((&mut de).readable()).unwrap();
+        assert_eq!(de.next_token_opt(), None);
+        assert_eq!(de_array.dims(), dim);
+
+        let mut de_array_vec = vec![0f32; de_array.elements()];
+        de_array.host(de_array_vec.as_mut_slice());
+        assert_eq!(&values[..], &de_array_vec[..]);
+    }
+}
+
+struct ComplexLaidOut<'a>(ComplexLayout, &'a Array);
+
+impl<'a> Serialize for ComplexLaidOut<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_complex(self.0, self.1, serializer)
+    }
+}
+
+#[test]
+fn test_array_complex_layout() {
+    let dim = Dim4::new(&[3, 1, 1, 1]);
+    let values = [
+        Complex::new(1.0f32, -1.0),
+        Complex::new(2.0, -2.0),
+        Complex::new(3.0, -3.0),
+    ];
+    let array = Array::new(&values, dim);
+
+    for &(layout, tag, data) in &[
+        (
+            ComplexLayout::Interleaved,
+            0,
+            [1.0, -1.0, 2.0, -2.0, 3.0, -3.0],
+        ),
+        (ComplexLayout::Planar, 1, [1.0, 2.0, 3.0, -1.0, -2.0, -3.0]),
+    ] {
+        let mut tokens = vec![
+            Token::Tuple { len: 5 },
+            Token::U16(1),
+            Token::Str("c32"),
+            Token::Tuple { len: 4 },
+            Token::U64(3),
+            Token::U64(1),
+            Token::U64(1),
+            Token::U64(1),
+            Token::TupleEnd,
+            Token::U8(tag),
+            Token::Seq { len: Some(6) },
+        ];
+        tokens.extend(data.iter().map(|&v| Token::F32(v)));
+        tokens.push(Token::SeqEnd);
+        tokens.push(Token::TupleEnd);
+        assert_ser_tokens(&ComplexLaidOut(layout, &array).readable(), &tokens);
+
+        let mut de = Deserializer::new(&tokens);
+        let de_array = deserialize_complex((&mut de).readable()).unwrap();
+        assert_eq!(de.next_token_opt(), None);
+        assert!(arrays_equal(&array, &de_array));
+    }
+
+    // an odd number of parts cannot be split into complex values
+    let tokens = [
+        Token::Tuple { len: 5 },
+        Token::U16(1),
+        Token::U8(1),
+        Token::Tuple { len: 4 },
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::U8(1),
+        Token::Seq { len: Some(3) },
+        Token::F32(1.0),
+        Token::F32(2.0),
+        Token::F32(3.0),
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    assert_eq!(
+        deserialize_complex((&mut de).readable())
+            .err()
+            .unwrap()
+            .to_string(),
+        "complex data holds an odd number of parts 3"
+    );
+}
+
+#[test]
+fn test_array_lenient_default_dtype() {
+    let tokens = [
+        Token::Map { len: Some(2) },
+        Token::Str("dims"),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Str("data"),
+        Token::Seq { len: Some(4) },
+        Token::F64(1.0),
+        Token::F64(2.0),
+        Token::F64(3.0),
+        Token::F64(4.0),
+        Token::SeqEnd,
+        Token::MapEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    let array = deserialize_lenient(&mut de).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(array.get_type(), DType::F32);
+    assert_eq!(array.dims(), Dim4::new(&[2, 2, 1, 1]));
+
+    let mut data = vec![0f32; array.elements()];
+    array.host(data.as_mut_slice());
+    assert_eq!(data, [1.0, 2.0, 3.0, 4.0]);
+
+    // given dims still have to match the data
+    let mut tokens = tokens.to_vec();
+    tokens[4] = Token::U64(3);
+    let mut de = Deserializer::new(&tokens);
+    assert!(deserialize_lenient(&mut de).is_err());
+}
+
+#[test]
+fn test_array_lenient_default_dims() {
+    let tokens = [
+        Token::Map { len: Some(2) },
+        Token::Str("dtype"),
+        Token::U8(5),
+        Token::Str("data"),
+        Token::Seq { len: Some(3) },
+        Token::I32(-1),
+        Token::I32(0),
+        Token::I32(7),
+        Token::SeqEnd,
+        Token::MapEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    let array = deserialize_lenient((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(array.get_type(), DType::S32);
+    assert_eq!(array.dims(), Dim4::new(&[3, 1, 1, 1]));
+
+    let mut data = vec![0i32; array.elements()];
+    array.host(data.as_mut_slice());
+    assert_eq!(data, [-1, 0, 7]);
+
+    // only `data` is required
+    let tokens = [
+        Token::Map { len: Some(1) },
+        Token::Str("data"),
+        Token::Seq { len: Some(2) },
+        Token::F32(0.5),
+        Token::F32(1.5),
+        Token::SeqEnd,
+        Token::MapEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    let array = deserialize_lenient((&mut de).readable()).unwrap();
+    assert_eq!(array.get_type(), DType::F32);
+    assert_eq!(array.dims(), Dim4::new(&[2, 1, 1, 1]));
+
+    let tokens = [
+        Token::Map { len: Some(1) },
+        Token::Str("dtype"),
+        Token::U8(0),
+        Token::MapEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    assert!(deserialize_lenient((&mut de).readable()).is_err());
+}
+
+#[test]
+fn test_array_backend_error() {
+    // a null handle makes every arrayfire call on the array fail
+    let array = Array::from(0i64);
+    assert_ser_tokens_error(
+        &Ser::new(&array).readable(),
+        &[],
+        "arrayfire backend error: Error message: One of the function arguments is incorrect",
+    );
+}
+
+#[test]
+fn test_array_backend_error_deserialize() {
+    // dims beyond arrayfire's signed `dim_t` cannot be created by the backend,
+    // even for an array without elements
+    let tokens = [
+        Token::Tuple { len: 3 },
+        Token::U16(1),
+        Token::Str("f32"),
+        Token::Tuple { len: 4 },
+        Token::U64(0),
+        Token::U64(1 << 63),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::TupleEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    let err = deserialize::<Array, _>((&mut de).readable()).err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "arrayfire backend error: array dims [0, 9223372036854775808, 1, 1] are out of range"
+    );
+}
+
+#[test]
+fn test_random_engine() {
+    let engine = RandomEngine::new(RandomEngineType::PHILOX_4X32_10, Some(42));
+    let tokens = [
+        Token::Tuple { len: 2 },
+        Token::U16(100),
+        Token::U64(42),
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&Ser::new(&engine), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let restored = deserialize::<RandomEngine, _>(&mut de).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(restored.get_type(), RandomEngineType::PHILOX_4X32_10);
+    assert_eq!(restored.get_seed(), 42);
+
+    let dim = Dim4::new(&[4, 4, 1, 1]);
+    let expected = random_uniform::<f32>(dim, engine);
+    let actual = random_uniform::<f32>(dim, restored);
+    let mut expected_vec = vec![0f32; expected.elements()];
+    let mut actual_vec = vec![0f32; actual.elements()];
+    expected.host(expected_vec.as_mut_slice());
+    actual.host(actual_vec.as_mut_slice());
+    assert_eq!(expected_vec, actual_vec);
+}
+
+#[test]
+fn test_array_in_place() {
+    let tokens = |rows: u64, cols: u64| {
+        let mut tokens = vec![
+            Token::Tuple { len: 4 },
+            Token::U16(1),
+            Token::Str("f64"),
+            Token::Tuple { len: 4 },
+            Token::U64(rows),
+            Token::U64(cols),
+            Token::U64(1),
+            Token::U64(1),
+            Token::TupleEnd,
+            Token::Seq { len: Some(4) },
+        ];
+        tokens.extend([1.0, 2.0, 3.0, 4.0].iter().map(|&v| Token::F64(v)));
+        tokens.push(Token::SeqEnd);
+        tokens.push(Token::TupleEnd);
+        tokens
+    };
+
+    let mut place = Array::new::<f64>(&[0.0; 4], Dim4::new(&[2, 2, 1, 1]));
+    let ptr = place.device_ptr();
+    place.unlock();
+
+    // matching dtype and dims: the data is written into the existing buffer
+    let same_shape = tokens(2, 2);
+    let mut de = Deserializer::new(&same_shape);
+    deserialize_in_place((&mut de).readable(), &mut place).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(place.device_ptr(), ptr);
+    place.unlock();
+
+    let mut data = vec![0f64; place.elements()];
+    place.host(data.as_mut_slice());
+    assert_eq!(data, [1.0, 2.0, 3.0, 4.0]);
+
+    // a different shape replaces the array
+    let new_shape = tokens(4, 1);
+    let mut de = Deserializer::new(&new_shape);
+    deserialize_in_place((&mut de).readable(), &mut place).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(place.dims(), Dim4::new(&[4, 1, 1, 1]));
+
+    let mut data = vec![0f64; place.elements()];
+    place.host(data.as_mut_slice());
+    assert_eq!(data, [1.0, 2.0, 3.0, 4.0]);
+}
+
+#[cfg(feature = "no-panic")]
+#[test]
+fn test_array_no_panic_dtypes() {
+    // `Array::get_type` asserts on the 16-bit dtypes, which must not abort serialization
+    let array = Array::new::<i16>(&[-1, 2], Dim4::new(&[2, 1, 1, 1]));
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("s16"),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(2) },
+        Token::I16(-1),
+        Token::I16(2),
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&Ser::new(&array).readable(), &tokens);
+
+    // an unknown dtype is an error rather than a panic
+    let mut tokens = tokens.to_vec();
+    tokens[2] = Token::U8(12);
+    let mut de = Deserializer::new(&tokens);
+    assert!(deserialize::<Array, _>((&mut de).readable()).is_err());
+}
+
+struct Shape(Dim4);
+
+impl Serialize for Shape {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        dim4_named::serialize(&self.0, serializer)
+    }
+}
+
+#[test]
+fn test_dim4_named() {
+    let dim = Dim4::new(&[1, 2, 3, 4]);
+    let tokens = [
+        Token::Struct {
+            name: "Dim4",
+            len: 4,
+        },
+        Token::Str("d0"),
+        Token::U64(1),
+        Token::Str("d1"),
+        Token::U64(2),
+        Token::Str("d2"),
+        Token::U64(3),
+        Token::Str("d3"),
+        Token::U64(4),
+        Token::StructEnd,
+    ];
+    assert_ser_tokens(&Shape(dim).readable(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let de_dim = dim4_named::deserialize((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(dim, de_dim);
+
+    // compact formats keep the tuple form
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U64(1),
+        Token::U64(2),
+        Token::U64(3),
+        Token::U64(4),
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&Shape(dim).compact(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let de_dim = dim4_named::deserialize((&mut de).compact()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(dim, de_dim);
+}
+
+struct FlexibleShape(Dim4);
+
+impl Serialize for FlexibleShape {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        dim4_flexible::serialize(&self.0, serializer)
+    }
+}
+
+#[test]
+fn test_dim4_flexible() {
+    let dim = Dim4::new(&[2, 3, 4, 5]);
+    let tokens = [
+        Token::Seq { len: Some(4) },
+        Token::U64(2),
+        Token::U64(3),
+        Token::U64(4),
+        Token::U64(5),
+        Token::SeqEnd,
+    ];
+    assert_ser_tokens(&FlexibleShape(dim), &tokens);
+    let mut de = Deserializer::new(&tokens);
+    assert_eq!(dim4_flexible::deserialize(&mut de).unwrap(), dim);
+    assert_eq!(de.next_token_opt(), None);
+
+    let tokens = [
+        Token::Seq { len: Some(2) },
+        Token::U64(28),
+        Token::U64(28),
+        Token::SeqEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    let de_dim = dim4_flexible::deserialize(&mut de).unwrap();
+    assert_eq!(de_dim, Dim4::new(&[28, 28, 1, 1]));
+
+    let tokens = [
+        Token::Seq { len: Some(3) },
+        Token::U64(28),
+        Token::U64(28),
+        Token::U64(3),
+        Token::SeqEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    let de_dim = dim4_flexible::deserialize(&mut de).unwrap();
+    assert_eq!(de_dim, Dim4::new(&[28, 28, 3, 1]));
+
+    let tokens = [Token::Seq { len: Some(0) }, Token::SeqEnd];
+    let mut de = Deserializer::new(&tokens);
+    assert!(dim4_flexible::deserialize(&mut de).is_err());
+
+    let tokens = [
+        Token::Seq { len: Some(5) },
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::SeqEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    assert!(dim4_flexible::deserialize(&mut de).is_err());
+}
+
+#[test]
+fn test_array_limited() {
+    let tokens = |d0: u64| {
+        vec![
+            Token::Tuple { len: 4 },
+            Token::U16(1),
+            Token::Str("f32"),
+            Token::Tuple { len: 4 },
+            Token::U64(d0),
+            Token::U64(1),
+            Token::U64(1),
+            Token::U64(1),
+            Token::TupleEnd,
+            Token::Seq { len: Some(2) },
+            Token::F32(1.0),
+            Token::F32(2.0),
+            Token::SeqEnd,
+            Token::TupleEnd,
+        ]
+    };
+
+    let within = tokens(2);
+    let mut de = Deserializer::new(&within);
+    let array = deserialize_limited(2, (&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert_eq!(array.elements(), 2);
+
+    // the declared dims are rejected before the data is read
+    for &(limit, d0) in &[(1, 2), (1 << 20, u64::MAX)] {
+        let oversized = tokens(d0);
+        let mut de = Deserializer::new(&oversized);
+        let err = deserialize_limited(limit, (&mut de).readable())
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            Error::TooLarge {
+                dims: [d0, 1, 1, 1],
+                max: limit,
+            }
+            .to_string()
+        );
+    }
+}
+
+#[test]
+fn test_array_strict() {
+    let tokens = |extra: &[Token]| {
+        let mut tokens = vec![
+            Token::Tuple { len: 4 },
+            Token::U16(1),
+            Token::Str("f32"),
+            Token::Tuple { len: 4 },
+            Token::U64(2),
+            Token::U64(1),
+            Token::U64(1),
+            Token::U64(1),
+            Token::TupleEnd,
+            Token::Seq { len: Some(2) },
+            Token::F32(1.0),
+            Token::F32(2.0),
+            Token::SeqEnd,
+        ];
+        tokens.extend_from_slice(extra);
+        tokens.push(Token::TupleEnd);
+        tokens
+    };
+
+    let exact = tokens(&[]);
+    let mut de = Deserializer::new(&exact);
+    let array = deserialize_strict((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert!(arrays_equal(
+        &array,
+        &Array::new(&[1.0f32, 2.0], Dim4::new(&[2, 1, 1, 1]))
+    ));
+
+    let extended = tokens(&[Token::Str("checksum")]);
+    let mut de = Deserializer::new(&extended);
+    let err = deserialize_strict((&mut de).readable()).err().unwrap();
+    assert_eq!(err.to_string(), "expected 4 elements, found more");
+}
+
+#[cfg(feature = "bincode")]
+#[derive(Deserialize)]
+struct Strict(#[serde(deserialize_with = "deserialize_strict")] Array);
+
+#[cfg(feature = "bincode")]
+#[test]
+fn test_array_strict_bincode() {
+    // bincode cannot skip values, so the empty tuple is not probed past its dims
+    let empty = Array::new_empty(Dim4::new(&[0, 1, 1, 1]), DType::F32);
+    let array = Array::new(&[1.0f32, 2.0], Dim4::new(&[2, 1, 1, 1]));
+    for array in &[empty, array] {
+        let bytes = bincode::serialize(&Ser::new(array)).unwrap();
+        let de_array = bincode::deserialize::<Strict>(&bytes).unwrap().0;
+        assert_eq!(de_array.elements(), array.elements());
+    }
+}
+
+#[test]
+fn test_array_overflowing_dims() {
+    // the element count of these dims overflows u64, or usize on 32-bit targets
+    let dims = if cfg!(target_pointer_width = "32") {
+        [1 << 16, 1 << 16, 1, 1]
+    } else {
+        [1 << 32, 1 << 32, 1, 1]
+    };
+    let expected = Error::TooLarge {
+        dims,
+        max: usize::MAX as u64,
+    }
+    .to_string();
+
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("f32"),
+        Token::Tuple { len: 4 },
+        Token::U64(dims[0]),
+        Token::U64(dims[1]),
+        Token::U64(dims[2]),
+        Token::U64(dims[3]),
+        Token::TupleEnd,
+        Token::Seq { len: Some(1) },
+        Token::F32(1.0),
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    let err = deserialize::<Array, _>((&mut de).readable()).err().unwrap();
+    assert_eq!(err.to_string(), expected);
+
+    // a binary header declaring the same dims
+    let mut bytes = Vec::new();
+    write_array(
+        &mut bytes,
+        &Array::new::<f32>(&[1.0], Dim4::new(&[1, 1, 1, 1])),
+    )
+    .unwrap();
+    for (i, d) in dims.iter().enumerate() {
+        bytes[8 + 8 * i..16 + 8 * i].copy_from_slice(&d.to_le_bytes());
+    }
+    match read_array(&mut bytes.as_slice()) {
+        Err(err @ Error::TooLarge { .. }) => assert_eq!(err.to_string(), expected),
+        Err(err) => panic!("unexpected error {}", err),
+        Ok(_) => panic!("oversized dims were accepted"),
+    }
+}
+
+#[test]
+fn test_typed_array() {
+    let dim = Dim4::new(&[3, 1, 1, 1]);
+    let array = Array::new::<f32>(&[1.0, 2.0, 3.0], dim);
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("f32"),
+        Token::Tuple { len: 4 },
+        Token::U64(3),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(3) },
+        Token::F32(1.0),
+        Token::F32(2.0),
+        Token::F32(3.0),
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    let typed = TypedArray::<f32>::new(array);
+    assert_ser_tokens(&(&typed).readable(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let de_typed = TypedArray::<f32>::deserialize((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    let de_array = de_typed.into_inner();
+    assert_eq!(de_array.dims(), dim);
+
+    let mut data = vec![0f32; de_array.elements()];
+    de_array.host(data.as_mut_slice());
+    assert_eq!(data, [1.0, 2.0, 3.0]);
+}
+
+#[test]
+fn test_typed_array_mismatch() {
+    // f32 data read as f64 fails on the dtype
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("f32"),
+        Token::Tuple { len: 4 },
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(1) },
+        Token::F32(1.0),
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    let mut de = Deserializer::new(&tokens);
+    let err = TypedArray::<f64>::deserialize((&mut de).readable())
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "dtype mismatch: expected F64, found F32");
+
+    // an f32 array wrapped as f64 is not serialized
+    let array = Array::new::<f32>(&[1.0], Dim4::new(&[1, 1, 1, 1]));
+    assert_ser_tokens_error(
+        &(&TypedArray::<f64>::new(array)).readable(),
+        &[],
+        "dtype mismatch: expected F64, found F32",
+    );
+}
+
+#[cfg(feature = "base64")]
+struct Base64<'a>(&'a Array);
+
+#[cfg(feature = "base64")]
+impl<'a> Serialize for Base64<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        arrayfire_serde::serialize_base64(self.0, serializer)
+    }
+}
+
+#[cfg(feature = "base64")]
+#[test]
+fn test_array_base64() {
+    let dim = Dim4::new(&[2, 1, 1, 1]);
+    let array = Array::new::<f32>(&[1.0, 2.0], dim);
+    let json = serde_json::to_string(&Base64(&array)).unwrap();
+    assert_eq!(
+        json,
+        r#"{"version":1,"dtype":"f32","dims":[2,1,1,1],"data":"AACAPwAAAEA="}"#
+    );
+
+    let mut de = serde_json::Deserializer::from_str(&json);
+    let de_array = arrayfire_serde::deserialize_base64(&mut de).unwrap();
+    assert_eq!(de_array.dims(), dim);
+    let mut data = vec![0f32; de_array.elements()];
+    de_array.host(data.as_mut_slice());
+    assert_eq!(data, [1.0, 2.0]);
+
+    // every padding length and a larger, bit packed array
+    for len in 1..20 {
+        let values: Vec<u8> = (0..len).map(|i| (i * 37) as u8).collect();
+        let array = Array::new::<u8>(&values, Dim4::new(&[len, 1, 1, 1]));
+        let json = serde_json::to_string(&Base64(&array)).unwrap();
+        let de_array =
+            arrayfire_serde::deserialize_base64(&mut serde_json::Deserializer::from_str(&json))
+                .unwrap();
+        let mut data = vec![0u8; de_array.elements()];
+        de_array.host(data.as_mut_slice());
+        assert_eq!(data, values);
+    }
+    let bits: Vec<bool> = (0..13).map(|i| i % 3 == 0).collect();
+    let array = Array::new::<bool>(&bits, Dim4::new(&[13, 1, 1, 1]));
+    let json = serde_json::to_string(&Base64(&array)).unwrap();
+    let de_array =
+        arrayfire_serde::deserialize_base64(&mut serde_json::Deserializer::from_str(&json))
+            .unwrap();
+    let mut data = vec![false; de_array.elements()];
+    de_array.host(data.as_mut_slice());
+    assert_eq!(data, bits);
+
+    let invalid = r#"{"version":1,"dtype":"f32","dims":[2,1,1,1],"data":"AACAPwAA*EA="}"#;
+    let mut de = serde_json::Deserializer::from_str(invalid);
+    assert!(arrayfire_serde::deserialize_base64(&mut de).is_err());
+}
+
+struct Mask(Option<Array>);
+
+impl Serialize for Mask {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        option::serialize(&self.0, serializer)
+    }
+}
+
+#[test]
+fn test_option() {
+    let array = Array::new::<f32>(&[1.0, 2.0], Dim4::new(&[2, 1, 1, 1]));
+    let tokens = [
+        Token::Some,
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("f32"),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(2) },
+        Token::F32(1.0),
+        Token::F32(2.0),
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&Mask(Some(array)).readable(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let de_array = option::deserialize::<Array, _>((&mut de).readable())
+        .unwrap()
+        .unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    let mut data = vec![0f32; de_array.elements()];
+    de_array.host(data.as_mut_slice());
+    assert_eq!(data, [1.0, 2.0]);
+
+    let tokens = [Token::None];
+    assert_ser_tokens(&Mask(None).readable(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let de_none = option::deserialize::<Array, _>((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert!(de_none.is_none());
+}
+
+struct Boxed(Box<Array>);
+
+impl Serialize for Boxed {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        boxed::serialize(&self.0, serializer)
+    }
+}
+
+#[test]
+fn test_boxed() {
+    let array = Box::new(Array::new::<f32>(&[1.0, 2.0], Dim4::new(&[2, 1, 1, 1])));
+    let tokens = [
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("f32"),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(2) },
+        Token::F32(1.0),
+        Token::F32(2.0),
+        Token::SeqEnd,
+        Token::TupleEnd,
+    ];
+    assert_ser_tokens(&Boxed(array.clone()).readable(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let de_array: Box<Array> = boxed::deserialize((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert!(arrays_equal(&array, &de_array));
+}
+
+struct Named<M>(M);
+
+impl<M> Serialize for Named<M>
+where
+    for<'m> &'m M: IntoIterator<Item = (&'m String, &'m Array)>,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        named_arrays::serialize(&self.0, serializer)
+    }
+}
+
+#[test]
+fn test_named_arrays() {
+    let scalar = Dim4::new(&[1, 1, 1, 1]);
+    let entries = vec![
+        ("fc.weight", Array::new::<f32>(&[0.5], scalar)),
+        ("bias", Array::new::<u8>(&[1], scalar)),
+        ("conv.weight", Array::new::<i32>(&[-2], scalar)),
+    ];
+    let entry_tokens = |name: &'static str, dtype: &'static str, element: Token| {
+        vec![
+            Token::Str(name),
+            Token::Tuple { len: 4 },
+            Token::U16(1),
+            Token::Str(dtype),
+            Token::Tuple { len: 4 },
+            Token::U64(1),
+            Token::U64(1),
+            Token::U64(1),
+            Token::U64(1),
+            Token::TupleEnd,
+            Token::Seq { len: Some(1) },
+            element,
+            Token::SeqEnd,
+            Token::TupleEnd,
+        ]
+    };
+    // sorted by name whatever the map type
+    let mut tokens = vec![Token::Map { len: Some(3) }];
+    tokens.extend(entry_tokens("bias", "u8", Token::U8(1)));
+    tokens.extend(entry_tokens("conv.weight", "s32", Token::I32(-2)));
+    tokens.extend(entry_tokens("fc.weight", "f32", Token::F32(0.5)));
+    tokens.push(Token::MapEnd);
+
+    let btree: BTreeMap<String, Array> = entries
+        .iter()
+        .map(|(name, array)| (name.to_string(), array.clone()))
+        .collect();
+    assert_ser_tokens(&Named(btree).readable(), &tokens);
+    let hash: HashMap<String, Array> = entries
+        .iter()
+        .map(|(name, array)| (name.to_string(), array.clone()))
+        .collect();
+    assert_ser_tokens(&Named(hash).readable(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let de_btree: BTreeMap<String, Array> =
+        named_arrays::deserialize((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    let mut de = Deserializer::new(&tokens);
+    let de_hash: HashMap<String, Array> = named_arrays::deserialize((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+
+    assert_eq!(de_btree.len(), 3);
+    assert_eq!(de_hash.len(), 3);
+    for (name, array) in &entries {
+        assert!(arrays_equal(array, &de_btree[*name]));
+        assert!(arrays_equal(array, &de_hash[*name]));
+    }
+}
+
+struct Weights((Array, Dim4, DType));
+
+impl Serialize for Weights {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        triple::serialize(&self.0, serializer)
+    }
+}
+
+#[test]
+fn test_triple() {
+    let array = Array::new::<u8>(&[1, 2], Dim4::new(&[2, 1, 1, 1]));
+    let value = (array, Dim4::new(&[1, 2, 1, 1]), DType::F64);
+    let tokens = [
+        Token::Tuple { len: 3 },
+        Token::Tuple { len: 4 },
+        Token::U16(1),
+        Token::Str("u8"),
+        Token::Tuple { len: 4 },
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Seq { len: Some(2) },
+        Token::U8(1),
+        Token::U8(2),
+        Token::SeqEnd,
+        Token::TupleEnd,
+        Token::Tuple { len: 4 },
+        Token::U64(1),
+        Token::U64(2),
+        Token::U64(1),
+        Token::U64(1),
+        Token::TupleEnd,
+        Token::Str("f64"),
+        Token::TupleEnd,
+    ];
+    let weights = Weights(value);
+    assert_ser_tokens(&(&weights).readable(), &tokens);
+
+    let mut de = Deserializer::new(&tokens);
+    let (de_array, de_dim, de_dtype) = triple::deserialize((&mut de).readable()).unwrap();
+    assert_eq!(de.next_token_opt(), None);
+    assert!(arrays_equal(&de_array, &(weights.0).0));
+    assert_eq!(de_dim, (weights.0).1);
+    assert_eq!(de_dtype, DType::F64);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_array_json() {
+    let dim = Dim4::new(&[2, 2, 1, 1]);
+    let array = Array::new::<i32>(&[1, -2, 3, -4], dim);
+
+    let mut json = Vec::new();
+    arrayfire_serde::json::to_writer(&mut json, &array).unwrap();
+    assert_eq!(json, br#"[1,"s32",[2,2,1,1],[1,-2,3,-4]]"#.to_vec());
+
+    let de_array = arrayfire_serde::json::from_reader(json.as_slice()).unwrap();
+    assert_eq!(de_array.get_type(), DType::S32);
+    assert_eq!(de_array.dims(), dim);
+    let mut data = vec![0i32; de_array.elements()];
+    de_array.host(data.as_mut_slice());
+    assert_eq!(data, [1, -2, 3, -4]);
+
+    match arrayfire_serde::json::from_reader(&b"[1,5"[..]) {
+        Err(Error::Format(_)) => {}
+        Err(err) => panic!("unexpected error {}", err),
+        Ok(_) => panic!("truncated json was accepted"),
+    }
+}
+
+#[cfg(feature = "rmp-serde")]
+#[test]
+fn test_array_msgpack() {
+    let values: Vec<u8> = (0..300).map(|i| i as u8).collect();
+    let dim = Dim4::new(&[300, 1, 1, 1]);
+    let array = Array::new(&values, dim);
+    // version 1, dtype tag 7, then dims with 300 as a uint 16
+    let header = [0x01, 0x07, 0x94, 0xcd, 0x01, 0x2c, 0x01, 0x01, 0x01];
+
+    let bytes = arrayfire_serde::msgpack::to_vec(&array, true).unwrap();
+    assert_eq!(bytes[0], 0x95);
+    assert_eq!(bytes[1..10], header);
+    // little-endian marker, then a bin 16 of 300 bytes
+    assert_eq!(bytes[10..14], [0x00, 0xc5, 0x01, 0x2c]);
+    assert_eq!(bytes[14..], values[..]);
+    let de_array = arrayfire_serde::msgpack::from_slice(&bytes).unwrap();
+    assert_eq!(de_array.dims(), dim);
+    assert!(arrays_equal(&array, &de_array));
+
+    let bytes = arrayfire_serde::msgpack::to_vec(&array, false).unwrap();
+    assert_eq!(bytes[0], 0x94);
+    assert_eq!(bytes[1..10], header);
+    if cfg!(not(feature = "zerocopy")) {
+        // an array 16 of 300 elements
+        assert_eq!(bytes[10..13], [0xdc, 0x01, 0x2c]);
+    }
+    let de_array = arrayfire_serde::msgpack::from_slice(&bytes).unwrap();
+    assert_eq!(de_array.dims(), dim);
+    assert!(arrays_equal(&array, &de_array));
+
+    let dim = Dim4::new(&[2, 1, 1, 1]);
+    let complex = Array::new(&[Complex::new(1.5f64, -2.0), Complex::new(0.0, 3.0)], dim);
+    for &binary in &[true, false] {
+        let bytes = arrayfire_serde::msgpack::to_vec(&complex, binary).unwrap();
+        let de_complex = arrayfire_serde::msgpack::from_slice(&bytes).unwrap();
+        assert!(arrays_equal(&complex, &de_complex));
+    }
+
+    match arrayfire_serde::msgpack::from_slice(&[0x94, 0x01]) {
+        Err(Error::Format(_)) => {}
+        Err(err) => panic!("unexpected error {}", err),
+        Ok(_) => panic!("truncated msgpack was accepted"),
+    }
+}